@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use crate::parser::{parse_sql_with, SqlAst};
+
+// 默认缓存的语句数量
+pub const DEFAULT_PARSE_CACHE_SIZE: usize = 64;
+
+// 解析结果的 LRU 缓存，键为规范化后的 SQL 文本
+#[derive(Debug)]
+pub struct ParseCache {
+    capacity: usize,
+    entries: VecDeque<(String, SqlAst)>,  // 队尾为最近使用
+    hits: usize,
+    misses: usize,
+    strict: bool,  // 严格模式：SQL 语法错误不再当作计算表达式
+}
+
+impl Default for ParseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PARSE_CACHE_SIZE)
+    }
+}
+
+impl ParseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+            strict: false,
+        }
+    }
+
+    // 命中缓存时直接返回，否则解析并缓存 SELECT 语句
+    pub fn parse(&mut self, sql: &str) -> Result<SqlAst, String> {
+        let key = normalize_sql(sql);
+        if let Some(pos) = self.entries.iter().position(|(k, _)| *k == key) {
+            self.hits += 1;
+            let entry = self.entries.remove(pos).unwrap();
+            let ast = entry.1.clone();
+            self.entries.push_back(entry);
+            return Ok(ast);
+        }
+
+        self.misses += 1;
+        let ast = parse_sql_with(sql, self.strict)?;
+        // 只缓存查询，写操作很少原样重复
+        if self.capacity > 0 && matches!(ast, SqlAst::Select { .. }) {
+            if self.entries.len() >= self.capacity {
+                self.entries.pop_front();
+            }
+            self.entries.push_back((key, ast.clone()));
+        }
+        Ok(ast)
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    // 表结构变化（CREATE/DROP）后清空缓存
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+// 规范化 SQL：去掉首尾空白和结尾分号，合并引号外的连续空白
+fn normalize_sql(sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    let mut last_space = false;
+
+    for c in sql.trim().trim_end_matches(';').trim_end().chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                result.push(c);
+            }
+            None if c.is_whitespace() => {
+                if !last_space {
+                    result.push(' ');
+                }
+                last_space = true;
+                continue;
+            }
+            None => {
+                if matches!(c, '\'' | '"' | '`') {
+                    quote = Some(c);
+                }
+                result.push(c);
+            }
+        }
+        last_space = false;
+    }
+
+    result
+}
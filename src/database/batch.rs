@@ -0,0 +1,20 @@
+use super::Database;
+use crate::error::DbError;
+use crate::{run_statement, QueryResult};
+
+impl Database {
+    // 按顺序执行多条语句并返回每条的结果，不打印也不保存；
+    // stop_on_error 为 true 时遇到第一个错误即停止，后面的语句不会执行
+    pub fn apply(&mut self, statements: &[&str], stop_on_error: bool) -> Vec<Result<QueryResult, DbError>> {
+        let mut results = Vec::with_capacity(statements.len());
+        for stmt in statements {
+            let result = run_statement(stmt.trim(), self);
+            let failed = result.is_err();
+            results.push(result);
+            if failed && stop_on_error {
+                break;
+            }
+        }
+        results
+    }
+}
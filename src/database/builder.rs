@@ -0,0 +1,46 @@
+use super::DataType;
+
+// 以链式调用的方式构造 create_table 所需的列定义
+//
+// let columns = TableBuilder::new()
+//     .column("id", DataType::Int(10)).primary_key().not_null()
+//     .column("name", DataType::Varchar(50))
+//     .build();
+// db.create_table("users", columns)?;
+#[derive(Debug, Default)]
+pub struct TableBuilder<'a> {
+    columns: Vec<(&'a str, DataType, bool, bool)>,
+}
+
+impl<'a> TableBuilder<'a> {
+    pub fn new() -> Self {
+        Self { columns: Vec::new() }
+    }
+
+    // 追加一列，后续的约束方法作用于该列
+    pub fn column(mut self, name: &'a str, data_type: DataType) -> Self {
+        self.columns.push((name, data_type, false, false));
+        self
+    }
+
+    // 将最后添加的列设为主键
+    pub fn primary_key(mut self) -> Self {
+        if let Some(col) = self.columns.last_mut() {
+            col.2 = true;
+        }
+        self
+    }
+
+    // 将最后添加的列设为非空
+    pub fn not_null(mut self) -> Self {
+        if let Some(col) = self.columns.last_mut() {
+            col.3 = true;
+        }
+        self
+    }
+
+    // 生成 (列名, 类型, 是否主键, 是否非空) 列表
+    pub fn build(self) -> Vec<(&'a str, DataType, bool, bool)> {
+        self.columns
+    }
+}
@@ -0,0 +1,104 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::Database;
+
+// 按 RFC 4180 解析 CSV：双引号包围的字段可以包含逗号和换行，"" 表示一个双引号；
+// 行尾可以是 \n 或 \r\n，末尾的空行会被忽略
+pub fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let mut record = Vec::new();
+        loop {
+            let field = if chars.peek() == Some(&'"') {
+                chars.next();
+                read_quoted(&mut chars, &mut line)?
+            } else {
+                read_plain(&mut chars)
+            };
+            record.push(field);
+
+            match chars.next() {
+                Some(',') => continue,
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    break;
+                }
+                Some('\n') | None => break,
+                Some(c) => return Err(format!("Unexpected character '{}' after quoted field on line {}", c, line)),
+            }
+        }
+        line += 1;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// 读取未加引号的字段，停在逗号或行尾之前
+fn read_plain(chars: &mut Peekable<Chars>) -> String {
+    let mut field = String::new();
+    while let Some(&c) = chars.peek() {
+        if c == ',' || c == '\n' || (c == '\r' && at_crlf(chars)) {
+            break;
+        }
+        field.push(c);
+        chars.next();
+    }
+    field
+}
+
+// \r 后面紧跟 \n 时才是行尾
+fn at_crlf(chars: &Peekable<Chars>) -> bool {
+    let mut ahead = chars.clone();
+    ahead.next();
+    ahead.peek() == Some(&'\n')
+}
+
+// 读取引号内的内容（开头的引号已被消费），停在结尾引号之后
+fn read_quoted(chars: &mut Peekable<Chars>, line: &mut usize) -> Result<String, String> {
+    let start = *line;
+    let mut field = String::new();
+    loop {
+        match chars.next() {
+            Some('"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            Some('"') => return Ok(field),
+            Some(c) => {
+                if c == '\n' {
+                    *line += 1;
+                }
+                field.push(c);
+            }
+            None => return Err(format!("Unterminated quoted field starting on line {}", start)),
+        }
+    }
+}
+
+impl Database {
+    // 导入 CSV 文本：第一行为列名，其余每行插入一行；空字段为 NULL。返回插入的行数
+    pub fn import_csv(&mut self, table_name: &str, input: &str) -> Result<usize, String> {
+        let mut records = parse_csv(input)?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(0);
+        };
+        let rows: Vec<Vec<String>> = records.collect();
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        // 整个导入只保存一个快照；逐行插入以便报告进度
+        self.check_writable()?;
+        self.take_snapshot();
+        for (i, row) in rows.iter().enumerate() {
+            self.insert_rows(table_name, Some(header.clone()), vec![row.iter().map(String::as_str).collect()])?;
+            if let Some(progress) = self.progress.as_mut() {
+                progress.tick(i + 1);
+            }
+        }
+        Ok(rows.len())
+    }
+}
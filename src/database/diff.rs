@@ -0,0 +1,137 @@
+use std::fmt;
+
+use super::{Column, DataType, Database};
+
+// 两个数据库之间的差异（以 self 为旧版本，other 为新版本）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DbDiff {
+    pub added_tables: Vec<String>,    // 仅存在于 other
+    pub removed_tables: Vec<String>,  // 仅存在于 self
+    pub column_changes: Vec<ColumnChange>,
+    pub row_count_changes: Vec<RowCountChange>,
+}
+
+// 同名表中列的变化，before/after 为列定义的描述，例如 INT(10) PRIMARY KEY
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnChange {
+    Added { table: String, column: String },
+    Removed { table: String, column: String },
+    Modified { table: String, column: String, before: String, after: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowCountChange {
+    pub table: String,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl DbDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_tables.is_empty()
+            && self.removed_tables.is_empty()
+            && self.column_changes.is_empty()
+            && self.row_count_changes.is_empty()
+    }
+}
+
+// 可读的摘要，每项差异一行
+impl fmt::Display for DbDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for table in &self.added_tables {
+            writeln!(f, "+ table {}", table)?;
+        }
+        for table in &self.removed_tables {
+            writeln!(f, "- table {}", table)?;
+        }
+        for change in &self.column_changes {
+            match change {
+                ColumnChange::Added { table, column } => writeln!(f, "+ column {}.{}", table, column)?,
+                ColumnChange::Removed { table, column } => writeln!(f, "- column {}.{}", table, column)?,
+                ColumnChange::Modified { table, column, before, after } => {
+                    writeln!(f, "~ column {}.{}: {} -> {}", table, column, before, after)?
+                }
+            }
+        }
+        for change in &self.row_count_changes {
+            writeln!(f, "~ rows {}: {} -> {}", change.table, change.before, change.after)?;
+        }
+        Ok(())
+    }
+}
+
+impl Database {
+    // 比较表、列定义和各表行数（表名和列名不区分大小写）
+    pub fn diff(&self, other: &Database) -> DbDiff {
+        let mut diff = DbDiff::default();
+
+        for table in self.tables_iter() {
+            let Some(new_table) = other.table(&table.name) else {
+                diff.removed_tables.push(table.name.clone());
+                continue;
+            };
+
+            for col in &table.columns {
+                match new_table.columns.iter().find(|c| c.name.eq_ignore_ascii_case(&col.name)) {
+                    None => diff.column_changes.push(ColumnChange::Removed {
+                        table: table.name.clone(),
+                        column: col.name.clone(),
+                    }),
+                    Some(new_col) if describe_column(new_col) != describe_column(col) => {
+                        diff.column_changes.push(ColumnChange::Modified {
+                            table: table.name.clone(),
+                            column: col.name.clone(),
+                            before: describe_column(col),
+                            after: describe_column(new_col),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for new_col in &new_table.columns {
+                if !table.columns.iter().any(|c| c.name.eq_ignore_ascii_case(&new_col.name)) {
+                    diff.column_changes.push(ColumnChange::Added {
+                        table: table.name.clone(),
+                        column: new_col.name.clone(),
+                    });
+                }
+            }
+
+            if table.data.len() != new_table.data.len() {
+                diff.row_count_changes.push(RowCountChange {
+                    table: table.name.clone(),
+                    before: table.data.len(),
+                    after: new_table.data.len(),
+                });
+            }
+        }
+
+        for table in other.tables_iter() {
+            if self.table(&table.name).is_none() {
+                diff.added_tables.push(table.name.clone());
+            }
+        }
+        diff
+    }
+}
+
+// 列定义的简短描述，用于比较和输出
+fn describe_column(col: &Column) -> String {
+    let mut desc = match col.data_type {
+        DataType::Int(width) => format!("INT({})", width),
+        DataType::Varchar(width) => format!("VARCHAR({})", width),
+    };
+    if col.is_primary {
+        desc.push_str(" PRIMARY KEY");
+    }
+    if col.not_null {
+        desc.push_str(" NOT NULL");
+    }
+    if let Some(default) = &col.default {
+        desc.push_str(&format!(" DEFAULT '{}'", default));
+    }
+    desc
+}
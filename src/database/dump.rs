@@ -0,0 +1,80 @@
+use super::{DataType, Database, Table, Value};
+
+impl Database {
+    // 生成可以重建整个数据库的 CREATE TABLE / INSERT 语句
+    pub fn to_sql(&self) -> String {
+        let mut out = String::new();
+        for table in self.tables_iter() {
+            out.push_str(&create_table_sql(table));
+            out.push('\n');
+            if let Some(insert) = insert_sql(table) {
+                out.push_str(&insert);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // 单个表的 CREATE TABLE 语句（只有结构），表不存在时返回 None
+    pub fn create_statement(&self, table_name: &str) -> Option<String> {
+        self.table(table_name).map(create_table_sql)
+    }
+}
+
+// 标识符统一用双引号包裹，保留字也能正确还原
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// 字符串字面量：单引号写成 ''，反斜杠写成 \\（INSERT 会还原反斜杠转义）
+fn quote_value(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+fn create_table_sql(table: &Table) -> String {
+    let columns: Vec<String> = table.columns.iter().map(|c| {
+        let mut def = format!("  {} ", quote_ident(&c.name));
+        match c.data_type {
+            DataType::Int(_) => def.push_str("INT"),
+            DataType::Varchar(len) => def.push_str(&format!("VARCHAR({})", len)),
+        }
+        if c.is_primary {
+            def.push_str(" PRIMARY KEY");
+        } else if c.not_null {
+            def.push_str(" NOT NULL");
+        }
+        if let Some(default) = &c.default {
+            def.push_str(" DEFAULT ");
+            match c.data_type {
+                DataType::Int(_) => def.push_str(default),
+                DataType::Varchar(_) => def.push_str(&quote_value(default)),
+            }
+        }
+        if let Some(comment) = &c.comment {
+            def.push_str(&format!(" COMMENT '{}'", comment.replace('\'', "''")));
+        }
+        def
+    }).collect();
+
+    format!("CREATE TABLE {} (\n{}\n);", quote_ident(&table.name), columns.join(",\n"))
+}
+
+fn insert_sql(table: &Table) -> Option<String> {
+    if table.data.is_empty() {
+        return None;
+    }
+
+    let rows: Vec<String> = table.data.iter().map(|row| {
+        let values: Vec<String> = row.iter().map(|value| {
+            match value {
+                Value::Null => "NULL".to_string(),
+                Value::Int(n) => n.to_string(),
+                Value::Text(s) => quote_value(s),
+            }
+        }).collect();
+        format!("  ({})", values.join(", "))
+    }).collect();
+
+    Some(format!("INSERT INTO {} VALUES\n{};", quote_ident(&table.name), rows.join(",\n")))
+}
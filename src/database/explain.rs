@@ -0,0 +1,93 @@
+use super::group::is_aggregate;
+use super::{split_nulls_order, Database, Join, JoinCondition, JoinKind, NULLS_FIRST, NULLS_LAST, RANDOM_ORDER_KEY};
+use crate::parser::SqlAst;
+
+// 查询计划中的一步，按执行顺序排列
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanStep {
+    pub operation: &'static str,  // SCAN、JOIN、FILTER、GROUP、SORT、DISTINCT、LIMIT、PROJECT
+    pub detail: String,
+}
+
+impl PlanStep {
+    fn new(operation: &'static str, detail: impl Into<String>) -> Self {
+        Self { operation, detail: detail.into() }
+    }
+}
+
+impl Database {
+    // 描述 SELECT 的执行步骤而不执行查询；目前没有索引，扫描总是全表扫描
+    pub fn explain(&self, ast: &SqlAst) -> Result<Vec<PlanStep>, String> {
+        let SqlAst::Select { table, joins, columns, where_clause, group_by, order_by, limit, offset, distinct_on, .. } = ast else {
+            return Err("EXPLAIN only supports SELECT".into());
+        };
+
+        let mut plan = Vec::new();
+        let scanned = self.table(table).ok_or(format!("Table '{}' not found", table))?;
+        plan.push(PlanStep::new("SCAN", format!("{} (full scan, {} rows)", scanned.name, scanned.data.len())));
+
+        for join in joins {
+            if !self.table_exists(&join.table) {
+                return Err(format!("Table '{}' not found", join.table));
+            }
+            plan.push(PlanStep::new("JOIN", describe_join(join)));
+        }
+        if let Some(cond) = where_clause {
+            plan.push(PlanStep::new("FILTER", cond.as_str()));
+        }
+        if !group_by.is_empty() {
+            plan.push(PlanStep::new("GROUP", group_by.join(", ")));
+        } else if columns.iter().any(|c| is_aggregate(c)) {
+            plan.push(PlanStep::new("GROUP", "all rows"));
+        }
+        if !order_by.is_empty() {
+            let keys: Vec<String> = order_by.iter()
+                .map(|(col, desc)| {
+                    if col == RANDOM_ORDER_KEY {
+                        return "random order".to_string();
+                    }
+                    let (col, nulls_first) = split_nulls_order(col);
+                    let direction = if *desc { " DESC" } else { "" };
+                    let nulls = match nulls_first {
+                        Some(true) => NULLS_FIRST,
+                        Some(false) => NULLS_LAST,
+                        None => "",
+                    };
+                    format!("{}{}{}", col, direction, nulls)
+                })
+                .collect();
+            plan.push(PlanStep::new("SORT", keys.join(", ")));
+        }
+        if !distinct_on.is_empty() {
+            plan.push(PlanStep::new("DISTINCT", format!("first row per ({})", distinct_on.join(", "))));
+        }
+        match (limit, offset) {
+            (Some(limit), Some(offset)) => plan.push(PlanStep::new("LIMIT", format!("{} offset {}", limit, offset))),
+            (Some(limit), None) => plan.push(PlanStep::new("LIMIT", limit.to_string())),
+            (None, Some(offset)) => plan.push(PlanStep::new("LIMIT", format!("offset {}", offset))),
+            (None, None) => {}
+        }
+        plan.push(PlanStep::new("PROJECT", columns.join(", ")));
+        Ok(plan)
+    }
+}
+
+// 例如 LEFT JOIN orders o ON u.id = o.user_id（嵌套循环）
+fn describe_join(join: &Join) -> String {
+    let kind = match join.kind {
+        JoinKind::Inner => "INNER",
+        JoinKind::Left => "LEFT",
+    };
+    let target = match &join.alias {
+        Some(alias) => format!("{} {}", join.table, alias),
+        None => join.table.clone(),
+    };
+    let condition = match &join.condition {
+        JoinCondition::On(pairs) => {
+            let conds: Vec<String> = pairs.iter().map(|(a, b)| format!("{} = {}", a, b)).collect();
+            format!("ON {}", conds.join(" AND "))
+        }
+        JoinCondition::Using(cols) => format!("USING ({})", cols.join(", ")),
+    };
+    format!("{} {} {} (nested loop)", kind, target, condition)
+}
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+// 自定义标量函数：参数和返回值都是显示形式的字符串（NULL 为空字符串）
+pub type ScalarFunction = Box<dyn Fn(&[String]) -> String + Send + Sync>;
+// 注册后共享给编译好的投影表达式
+pub(crate) type SharedFunction = Arc<dyn Fn(&[String]) -> String + Send + Sync>;
+
+// 已注册的自定义函数，函数名统一为大写
+#[derive(Default, Clone)]
+pub(crate) struct FunctionRegistry {
+    functions: HashMap<String, SharedFunction>,
+}
+
+impl FunctionRegistry {
+    pub(crate) fn register(&mut self, name: &str, func: ScalarFunction) {
+        self.functions.insert(name.to_uppercase(), Arc::from(func));
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<SharedFunction> {
+        self.functions.get(&name.to_uppercase()).cloned()
+    }
+}
+
+impl fmt::Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.functions.keys()).finish()
+    }
+}
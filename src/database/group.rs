@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use super::value::{compare_for_sort, compare_nullable};
+use super::{split_nulls_order, DataType, Database, Table, Value};
+
+// 支持的聚合函数
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AggregateKind {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+// 聚合表达式，column 为 None 表示 COUNT(*)
+#[derive(Debug, Clone)]
+struct Aggregate {
+    kind: AggregateKind,
+    column: Option<usize>,
+}
+
+// 分组查询的结果列：分组列或聚合值
+#[derive(Debug, Clone)]
+enum GroupedColumn {
+    Key(usize),  // 在分组键中的位置
+    Aggregate(Aggregate),
+}
+
+// 分组查询的排序依据
+enum GroupSortKey {
+    Key(usize),
+    Aggregate(usize),  // 在 projections 中的位置
+}
+
+// 判断结果列是否为聚合函数，如 COUNT(*)、SUM(age)
+pub(super) fn is_aggregate(expr: &str) -> bool {
+    parse_aggregate_call(expr).is_some()
+}
+
+// 拆出聚合函数名和参数文本
+fn parse_aggregate_call(expr: &str) -> Option<(AggregateKind, &str)> {
+    let expr = expr.trim();
+    let open = expr.find('(')?;
+    let inner = expr[open + 1..].strip_suffix(')')?.trim();
+    let kind = match expr[..open].trim().to_uppercase().as_str() {
+        "COUNT" => AggregateKind::Count,
+        "SUM" => AggregateKind::Sum,
+        "MIN" => AggregateKind::Min,
+        "MAX" => AggregateKind::Max,
+        "AVG" => AggregateKind::Avg,
+        _ => return None,
+    };
+    Some((kind, inner))
+}
+
+impl Aggregate {
+    fn compile(expr: &str, table: &Table) -> Result<Option<Self>, String> {
+        let Some((kind, arg)) = parse_aggregate_call(expr) else {
+            return Ok(None);
+        };
+        if arg == "*" {
+            if kind != AggregateKind::Count {
+                return Err(format!("Invalid use of * in {}", expr));
+            }
+            return Ok(Some(Self { kind, column: None }));
+        }
+
+        let idx = table.column_index(arg)
+            .ok_or(format!("Column '{}' not found", arg))?;
+        let is_int = matches!(table.columns[idx].data_type, DataType::Int(_));
+        if matches!(kind, AggregateKind::Sum | AggregateKind::Avg) && !is_int {
+            return Err(format!("{} requires an INT column", expr));
+        }
+        Ok(Some(Self { kind, column: Some(idx) }))
+    }
+
+    // 对一组行求值，NULL 不参与计算
+    fn eval(&self, rows: &[&Vec<Value>]) -> String {
+        let Some(idx) = self.column else {
+            return rows.len().to_string();
+        };
+        let values: Vec<&Value> = rows.iter()
+            .map(|row| &row[idx])
+            .filter(|v| !v.is_null())
+            .collect();
+        let sum = || values.iter()
+            .map(|v| if let Value::Int(n) = v { *n } else { 0 })
+            .sum::<i64>();
+
+        match self.kind {
+            AggregateKind::Count => values.len().to_string(),
+            AggregateKind::Sum | AggregateKind::Avg if values.is_empty() => String::new(),
+            AggregateKind::Sum => sum().to_string(),
+            AggregateKind::Avg => (sum() as f64 / values.len() as f64).to_string(),
+            AggregateKind::Min => values.into_iter().min().map(Value::to_string).unwrap_or_default(),
+            AggregateKind::Max => values.into_iter().max().map(Value::to_string).unwrap_or_default(),
+        }
+    }
+}
+
+// 聚合结果按数值比较（AVG 可能是小数）
+fn compare_numeric(a: &str, b: &str) -> Ordering {
+    let a = a.trim().parse::<f64>().unwrap_or(0.0);
+    let b = b.trim().parse::<f64>().unwrap_or(0.0);
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+impl Database {
+    // 按表名执行分组查询（group_by 为空且没有聚合函数时等同于 select）
+    #[allow(clippy::too_many_arguments)]
+    pub fn select_grouped(
+        &self,
+        table_name: &str,
+        columns: Vec<&str>,
+        condition: Option<&str>,
+        group_by: Vec<&str>,
+        order_by: Option<Vec<(&str, bool)>>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<Vec<String>>, String> {
+        let table = self.tables
+            .iter()
+            .find(|t| t.name == table_name)
+            .ok_or("Table not found")?;
+        self.select_grouped_from(table, columns, condition, group_by, order_by, limit, offset)
+    }
+
+    // 分组查询：每个分组输出一行；没有 GROUP BY 的聚合查询视为只有一个分组
+    #[allow(clippy::too_many_arguments)]
+    pub fn select_grouped_from(
+        &self,
+        table: &Table,
+        columns: Vec<&str>,
+        condition: Option<&str>,
+        group_by: Vec<&str>,
+        order_by: Option<Vec<(&str, bool)>>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<Vec<String>>, String> {
+        // 既没有分组也没有聚合时就是普通查询
+        if group_by.is_empty() && !columns.iter().any(|col| is_aggregate(col)) {
+            return self.select_from(table, columns, condition, order_by, limit, offset);
+        }
+
+        let key_indices: Vec<usize> = group_by.iter()
+            .map(|col| table.column_index(col).ok_or(format!("Column '{}' not found", col)))
+            .collect::<Result<_, _>>()?;
+
+        // 结果列只能是分组列或聚合函数
+        let projections: Vec<GroupedColumn> = columns.iter().map(|col| {
+            if let Some(agg) = Aggregate::compile(col, table)? {
+                return Ok(GroupedColumn::Aggregate(agg));
+            }
+            table.column_index(col)
+                .and_then(|idx| key_indices.iter().position(|k| *k == idx))
+                .map(GroupedColumn::Key)
+                .ok_or(format!("Column '{}' must appear in GROUP BY or be used in an aggregate", col))
+        }).collect::<Result<_, String>>()?;
+
+        // 排序列只能是分组列或结果中的聚合函数
+        let sort_specs: Vec<(GroupSortKey, bool, Option<bool>)> = order_by.unwrap_or_default().into_iter().map(|(col, desc)| {
+            let (col, nulls_first) = split_nulls_order(col);
+            if let Some(pos) = table.column_index(col).and_then(|idx| key_indices.iter().position(|k| *k == idx)) {
+                return Ok((GroupSortKey::Key(pos), desc, nulls_first));
+            }
+            let wanted = normalize_expr(col);
+            columns.iter()
+                .position(|c| is_aggregate(c) && normalize_expr(c) == wanted)
+                .map(|pos| (GroupSortKey::Aggregate(pos), desc, nulls_first))
+                .ok_or(format!("ORDER BY column '{}' must appear in GROUP BY or be an aggregate", col))
+        }).collect::<Result<_, String>>()?;
+
+        let filter_fn = self.row_filter(table, condition)?;
+
+        // 按首次出现的顺序收集分组
+        let mut groups: Vec<(Vec<Value>, Vec<&Vec<Value>>)> = Vec::new();
+        let mut positions: HashMap<Vec<Value>, usize> = HashMap::new();
+        for row in table.data.iter().filter(|row| filter_fn(row)) {
+            let key: Vec<Value> = key_indices.iter().map(|&i| row[i].clone()).collect();
+            match positions.get(&key) {
+                Some(&pos) => groups[pos].1.push(row),
+                None => {
+                    positions.insert(key.clone(), groups.len());
+                    groups.push((key, vec![row]));
+                }
+            }
+        }
+        // 没有 GROUP BY 时即使没有任何行也输出一行聚合结果
+        if key_indices.is_empty() && groups.is_empty() {
+            groups.push((Vec::new(), Vec::new()));
+        }
+
+        let mut result: Vec<(Vec<Value>, Vec<String>)> = groups.iter().map(|(key, rows)| {
+            let values = projections.iter().map(|p| match p {
+                GroupedColumn::Key(pos) => key[*pos].to_string(),
+                GroupedColumn::Aggregate(agg) => agg.eval(rows),
+            }).collect();
+            (key.clone(), values)
+        }).collect();
+
+        result.sort_by(|(a_key, a_values), (b_key, b_values)| {
+            for (spec, desc, nulls_first) in &sort_specs {
+                let ordering = match spec {
+                    GroupSortKey::Key(pos) => compare_for_sort(&a_key[*pos], &b_key[*pos], *desc, *nulls_first),
+                    // 聚合结果为空字符串表示 NULL（例如全为 NULL 的 MAX）
+                    GroupSortKey::Aggregate(pos) => {
+                        let (a, b) = (&a_values[*pos], &b_values[*pos]);
+                        compare_nullable(a.is_empty(), b.is_empty(), *desc, *nulls_first, || compare_numeric(a, b))
+                    }
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+
+        let rows = result.into_iter()
+            .map(|(_, values)| values)
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(rows)
+    }
+}
+
+// 比较聚合表达式时忽略大小写和空白
+fn normalize_expr(expr: &str) -> String {
+    expr.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase()
+}
@@ -0,0 +1,104 @@
+use super::{Column, Table, Value};
+
+// 连接条件：ON 中由 AND 连接的等值比较，或 USING 的同名列
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinCondition {
+    On(Vec<(String, String)>),
+    Using(Vec<String>),
+}
+
+// 连接类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinKind {
+    Inner,
+    Left,  // 左表未匹配的行保留，右表列填 NULL
+}
+
+// 一个 JOIN 子句
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: String,
+    pub alias: Option<String>,
+    pub condition: JoinCondition,
+}
+
+// 复制表数据，列名加上限定符，例如 u.id
+pub(crate) fn qualify(table: &Table, qualifier: &str) -> Table {
+    Table {
+        name: qualifier.to_string(),
+        columns: table.columns.iter()
+            .map(|c| Column {
+                name: format!("{}.{}", qualifier, c.name),
+                ..c.clone()
+            })
+            .collect(),
+        data: table.data.clone(),
+    }
+}
+
+impl Join {
+    // 用嵌套循环把右表连接到已有结果上
+    pub(crate) fn apply(&self, left: Table, right: Table) -> Result<Table, String> {
+        let left_len = left.columns.len();
+        let mut columns = left.columns.clone();
+        columns.extend(right.columns.iter().cloned());
+        let combined = Table { name: left.name.clone(), columns, data: vec![] };
+
+        // 每一对需要相等的列（在拼接后的行中的下标），以及 USING 时要合并掉的右表列
+        let mut pairs = Vec::new();
+        let mut collapsed = Vec::new();
+        match &self.condition {
+            JoinCondition::On(conds) => {
+                for (a, b) in conds {
+                    let a_idx = combined.column_index(a)
+                        .ok_or(format!("Column '{}' not found in join", a))?;
+                    let b_idx = combined.column_index(b)
+                        .ok_or(format!("Column '{}' not found in join", b))?;
+                    pairs.push((a_idx, b_idx));
+                }
+            }
+            JoinCondition::Using(names) => {
+                for name in names {
+                    let l_idx = left.column_index(name)
+                        .ok_or(format!("Column '{}' not found in left side of USING", name))?;
+                    let r_idx = right.column_index(name)
+                        .ok_or(format!("Column '{}' not found in table '{}'", name, right.name))?;
+                    pairs.push((l_idx, left_len + r_idx));
+                    collapsed.push(left_len + r_idx);
+                }
+            }
+        }
+
+        let mut data = Vec::new();
+        let null_right = vec![Value::Null; right.columns.len()];
+        for l_row in &left.data {
+            let mut matched = false;
+            for r_row in &right.data {
+                let row: Vec<Value> = l_row.iter().chain(r_row).cloned().collect();
+                // NULL 与任何值都不相等
+                // 两侧列类型可能不同，按显示形式比较
+                if pairs.iter().all(|&(a, b)| !row[a].is_null() && row[a].to_string() == row[b].to_string()) {
+                    data.push(row);
+                    matched = true;
+                }
+            }
+            if !matched && self.kind == JoinKind::Left {
+                data.push(l_row.iter().chain(&null_right).cloned().collect());
+            }
+        }
+
+        // USING 的同名列只保留左表的一份
+        let keep = |idx: &usize| !collapsed.contains(idx);
+        let columns = combined.columns.into_iter()
+            .enumerate()
+            .filter(|(i, _)| keep(i))
+            .map(|(_, c)| c)
+            .collect();
+        let data = data.into_iter()
+            .map(|row| row.into_iter().enumerate().filter(|(i, _)| keep(i)).map(|(_, v)| v).collect())
+            .collect();
+
+        Ok(Table { name: left.name, columns, data })
+    }
+}
@@ -0,0 +1,78 @@
+// LIKE 模式中的一个元素
+#[derive(Debug, Clone, PartialEq)]
+enum LikeToken {
+    AnySequence,  // %
+    AnyChar,      // _
+    Literal(char),
+}
+
+// 编译后的 LIKE 模式
+#[derive(Debug, Clone)]
+pub(super) struct LikePattern {
+    tokens: Vec<LikeToken>,
+    ignore_case: bool,  // ILIKE：逐字符比较时忽略大小写
+}
+
+impl LikePattern {
+    // escape 为 None 时 % 和 _ 总是通配符
+    pub(super) fn compile(pattern: &str, escape: Option<char>) -> Result<Self, String> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                c if Some(c) == escape => match chars.next() {
+                    Some(next) => LikeToken::Literal(next),
+                    None => return Err(format!("LIKE pattern must not end with escape character: {}", pattern)),
+                },
+                '%' => LikeToken::AnySequence,
+                '_' => LikeToken::AnyChar,
+                c => LikeToken::Literal(c),
+            };
+            tokens.push(token);
+        }
+        Ok(Self { tokens, ignore_case: false })
+    }
+
+    // 改为忽略大小写的匹配（ILIKE）
+    pub(super) fn ignoring_case(self) -> Self {
+        Self { ignore_case: true, ..self }
+    }
+
+    fn chars_equal(&self, a: char, b: char) -> bool {
+        a == b || (self.ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+    }
+
+    // 贪心匹配，遇到不匹配时回退到上一个 % 处
+    pub(super) fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let (mut t, mut p) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while t < text.len() {
+            match self.tokens.get(p) {
+                Some(LikeToken::AnySequence) => {
+                    p += 1;
+                    backtrack = Some((p, t));
+                }
+                Some(LikeToken::AnyChar) => {
+                    p += 1;
+                    t += 1;
+                }
+                Some(LikeToken::Literal(c)) if self.chars_equal(*c, text[t]) => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match backtrack {
+                    // 让上一个 % 多吞一个字符
+                    Some((bp, bt)) => {
+                        p = bp;
+                        t = bt + 1;
+                        backtrack = Some((bp, bt + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.tokens[p..].iter().all(|token| *token == LikeToken::AnySequence)
+    }
+}
@@ -0,0 +1,44 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::DbError;
+
+// 数据库文件锁：持有期间其他进程无法打开同一个数据库，Drop 时自动释放
+#[derive(Debug)]
+pub struct DbLock {
+    path: PathBuf,
+}
+
+impl DbLock {
+    // 为数据库文件获取锁（锁文件与数据库同目录，扩展名为 .lock）
+    pub fn acquire(db_path: &Path) -> Result<Self, DbError> {
+        let lock_path = db_path.with_extension("lock");
+        if let Some(dir) = lock_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| DbError::Io(e.to_string()))?;
+        }
+
+        // create_new 保证只有一个进程能创建成功
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AlreadyExists => DbError::Locked(lock_path.display().to_string()),
+                _ => DbError::Io(format!("Failed to create lock file: {}", e)),
+            })?;
+        let _ = writeln!(file, "{}", std::process::id());
+
+        Ok(Self { path: lock_path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for DbLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use super::{Database, Value};
+use crate::error::DbError;
+
+impl Database {
+    // 合并另一个数据库：同名表结构必须一致并追加行，仅存在于 other 的表直接加入
+    // 主键冲突时 skip_conflicts 为 true 则跳过该行，否则报错且不做任何修改
+    pub fn merge(&mut self, other: Database, skip_conflicts: bool) -> Result<(), DbError> {
+        self.check_writable()?;
+        // 先检查所有表，确认可以合并后再修改
+        let mut pending = Vec::new();
+        for table in other.tables {
+            let Some(existing) = self.table(&table.name) else {
+                pending.push((None, Vec::new(), table));
+                continue;
+            };
+            if existing.columns != table.columns {
+                return Err(DbError::SchemaMismatch(table.name));
+            }
+
+            let pk_index = existing.columns.iter().position(|c| c.is_primary);
+            let mut keys: HashSet<Value> = pk_index
+                .map(|idx| existing.data.iter().map(|row| row[idx].clone()).collect())
+                .unwrap_or_default();
+            let mut rows = Vec::new();
+            for row in &table.data {
+                let duplicate = pk_index.filter(|&idx| !keys.insert(row[idx].clone()));
+                if let Some(idx) = duplicate {
+                    if skip_conflicts {
+                        continue;
+                    }
+                    return Err(DbError::DuplicateKey(table.name, row[idx].to_string()));
+                }
+                rows.push(row.clone());
+            }
+            let target = self.tables.iter().position(|t| t.name.eq_ignore_ascii_case(&table.name));
+            pending.push((target, rows, table));
+        }
+
+        self.take_snapshot();
+        for (target, rows, table) in pending {
+            match target {
+                Some(idx) => self.tables[idx].data.extend(rows),
+                None => self.tables.push(table),
+            }
+        }
+        self.parse_cache.clear();
+        Ok(())
+    }
+}
@@ -1,16 +1,117 @@
 use serde::{Serialize, Deserialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use crate::cache::ParseCache;
+use crate::error::DbError;
 use crate::history::CommandHistory;
 
+// 默认的数据库文件路径
+pub const DEFAULT_DB_PATH: &str = "data/db.json";
+// 默认的命令历史文件路径
+pub const DEFAULT_HISTORY_PATH: &str = "data/history.json";
+
+mod batch;
+mod builder;
+mod csv;
+mod diff;
+mod dump;
+mod explain;
+mod function;
+mod group;
+mod join;
+mod like;
+mod lock;
+mod merge;
+mod progress;
+mod projection;
+mod random;
+mod sample;
+mod schema;
+mod validate;
+mod value;
+pub use builder::TableBuilder;
+pub use csv::parse_csv;
+pub use diff::{ColumnChange, DbDiff, RowCountChange};
+pub use explain::PlanStep;
+pub use join::{Join, JoinCondition, JoinKind};
+pub use lock::DbLock;
+use function::FunctionRegistry;
+pub use function::ScalarFunction;
+use like::LikePattern;
+use progress::ProgressReporter;
+pub use progress::ProgressCallback;
+use projection::ProjectionExpr;
+use random::SplitMix64;
+pub use validate::IntegrityError;
+pub use value::Value;
+
+// ORDER BY RANDOM() / RAND() 在排序列表中的表示
+pub const RANDOM_ORDER_KEY: &str = "RANDOM()";
+// ORDER BY 列后可以跟 NULLS FIRST / NULLS LAST，例如 ("age NULLS LAST", false)
+pub const NULLS_FIRST: &str = " NULLS FIRST";
+pub const NULLS_LAST: &str = " NULLS LAST";
+// INSERT 中 DEFAULT 关键字的表示（不会与普通字符串值冲突）
+pub const DEFAULT_VALUE: &str = "\u{0}DEFAULT";
+// UPDATE SET 中以此开头的值是表达式（例如列名），按更新前的行求值
+pub const SET_EXPRESSION_PREFIX: &str = "\u{0}EXPR ";
+
 // 为所有需要序列化的类型添加derive
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Database {
-    pub tables: Vec<Table>,
+    tables: Vec<Table>,
     #[serde(default)]
     pub command_history: Vec<String>,     // 历史记录
     #[serde(skip)]
     pub snapshots: Vec<DatabaseSnapshot>, // 快照栈
+    #[serde(skip)]
+    path: Option<PathBuf>,                // 存储路径，None 时使用 DEFAULT_DB_PATH
+    #[serde(skip)]
+    lock: Option<DbLock>,                 // 加载后持有的文件锁
+    #[serde(skip)]
+    random_seed: Option<u64>,             // ORDER BY RANDOM() 的固定种子（测试用）
+    #[serde(skip)]
+    parse_cache: ParseCache,              // 重复查询的解析缓存
+    #[serde(skip)]
+    read_only: bool,                      // 只读模式下拒绝所有修改和保存
+    #[serde(skip)]
+    compact_json: bool,                   // 保存为紧凑 JSON（默认带缩进便于手工编辑）
+    #[serde(skip)]
+    functions: FunctionRegistry,          // 自定义标量函数
+    #[serde(skip)]
+    progress: Option<ProgressReporter>,   // UPDATE/DELETE/CSV 导入的进度回调
+    #[serde(skip)]
+    trim_varchar: bool,                   // INSERT/UPDATE 时去掉 VARCHAR 值首尾的空白
+    #[serde(skip)]
+    dropped: bool,                        // DROP DATABASE 之后、再次建表之前不写文件
+}
+
+// OPTIMIZE TABLE 的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimizeStats {
+    pub table: String,
+    pub rows: usize,
+    pub index_entries: usize,  // 重建后的主键索引条目数（没有主键时为 0）
+}
+
+// ANALYZE 输出的单列统计信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub column: String,
+    pub total: usize,     // 总行数
+    pub nulls: usize,     // NULL 值数量
+    pub distinct: usize,  // 不同的非 NULL 值数量
+}
+
+// column_stats 返回的数值列统计；没有非 NULL 值时 min/max/avg 为 None
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericStats {
+    pub column: String,
+    pub count: usize,  // 非 NULL 值数量
+    pub nulls: usize,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub avg: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -21,28 +122,92 @@ pub struct DatabaseSnapshot {
 #[derive(Debug, Clone)]
 pub struct TableSnapshot {
     pub name: String,
-    pub data: Vec<Vec<String>>,
+    pub data: Vec<Vec<Value>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
-    pub data: Vec<Vec<String>>,  // Vec<String> 本身是可序列化的
+    pub data: Vec<Vec<Value>>,  // 按列类型解析后的值，序列化为字符串
+}
+
+// 拆出 ORDER BY 列后的 NULLS FIRST / NULLS LAST（不区分大小写）
+pub(crate) fn split_nulls_order(col: &str) -> (&str, Option<bool>) {
+    for (suffix, nulls_first) in [(NULLS_FIRST, true), (NULLS_LAST, false)] {
+        let split = col.len().checked_sub(suffix.len())
+            .filter(|&at| col.is_char_boundary(at) && col[at..].eq_ignore_ascii_case(suffix));
+        if let Some(at) = split {
+            return (&col[..at], Some(nulls_first));
+        }
+    }
+    (col, None)
+}
+
+// 拆分 模式.表名；没有模式时第一项为 None
+pub fn split_schema(name: &str) -> (Option<&str>, &str) {
+    match name.split_once('.') {
+        Some((schema, table)) => (Some(schema), table),
+        None => (None, name),
+    }
+}
+
+impl Table {
+    // 只有列定义、以别名命名的副本，用于解析 UPDATE/DELETE 中的 别名.列
+    pub(crate) fn aliased(&self, alias: &str) -> Table {
+        Table {
+            name: alias.to_string(),
+            columns: self.columns.clone(),
+            data: Vec::new(),
+        }
+    }
+
+    // 按列名查找列索引（不区分大小写，完全相同的列名优先）；
+    // 支持 表名.列名 和 模式.表名.列名，连接结果中的列名按唯一后缀匹配
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        if let Some(idx) = self.columns.iter().position(|c| c.name == name) {
+            return Some(idx);
+        }
+        if let Some(idx) = self.columns.iter().position(|c| c.name.eq_ignore_ascii_case(name)) {
+            return Some(idx);
+        }
+        if let Some((qualifier, column)) = name.rsplit_once('.') {
+            let (_, table) = split_schema(&self.name);
+            let own_column = (qualifier.eq_ignore_ascii_case(&self.name) || qualifier.eq_ignore_ascii_case(table))
+                .then(|| self.columns.iter().position(|c| c.name.eq_ignore_ascii_case(column)))
+                .flatten();
+            if own_column.is_some() {
+                return own_column;
+            }
+        }
+
+        let suffix = format!(".{}", name.to_lowercase());
+        let mut matches = self.columns.iter()
+            .enumerate()
+            .filter(|(_, c)| c.name.to_lowercase().ends_with(&suffix));
+        match (matches.next(), matches.next()) {
+            (Some((idx, _)), None) => Some(idx),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum DataType {
     Int(u32),
     Varchar(u32),
 }
 
-#[derive(Debug, Serialize, Deserialize,Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
     pub is_primary: bool,
     pub not_null: bool,
+    #[serde(default)]
+    pub default: Option<String>,  // 默认值，None 表示省略时为 NULL
+    #[serde(default)]
+    pub comment: Option<String>,  // 列注释，只用于说明（旧文件中没有此字段）
 }
 
 impl Database {
@@ -51,7 +216,229 @@ impl Database {
             tables: Vec::new(),
             command_history: Vec::new(),
             snapshots: Vec::new(),
+            path: None,
+            lock: None,
+            random_seed: None,
+            parse_cache: ParseCache::default(),
+            read_only: false,
+            compact_json: false,
+            functions: FunctionRegistry::default(),
+            progress: None,
+            trim_varchar: false,
+            dropped: false,
+        }
+    }
+
+    // 通过缓存解析 SQL 语句
+    pub fn parse_cached(&mut self, sql: &str) -> Result<crate::parser::SqlAst, String> {
+        self.parse_cache.parse(sql)
+    }
+
+    pub fn parse_cache(&self) -> &ParseCache {
+        &self.parse_cache
+    }
+
+    // 严格模式下拼错或不支持的 SQL 语句报语法错误，而不是当作计算表达式
+    pub fn set_strict_mode(&mut self, on: bool) {
+        self.parse_cache.set_strict(on);
+    }
+
+    pub fn strict_mode(&self) -> bool {
+        self.parse_cache.is_strict()
+    }
+
+    // 固定 ORDER BY RANDOM() 的随机种子，使结果可复现
+    pub fn set_random_seed(&mut self, seed: Option<u64>) {
+        self.random_seed = seed;
+    }
+
+    // 只读模式：修改表结构/数据以及保存都会返回 DbError::ReadOnly
+    pub fn set_read_only(&mut self, on: bool) {
+        self.read_only = on;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self) -> Result<(), DbError> {
+        if self.read_only {
+            return Err(DbError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    // 保存时使用紧凑 JSON 还是带缩进的 JSON
+    pub fn set_compact_json(&mut self, on: bool) {
+        self.compact_json = on;
+    }
+
+    pub fn compact_json(&self) -> bool {
+        self.compact_json
+    }
+
+    // 开启后 INSERT/UPDATE 写入的 VARCHAR 值去掉首尾空白（只有空白的值变为 NULL）
+    pub fn set_trim_varchar(&mut self, on: bool) {
+        self.trim_varchar = on;
+    }
+
+    pub fn trim_varchar(&self) -> bool {
+        self.trim_varchar
+    }
+
+    // UPDATE/DELETE/CSV 导入每处理 every 行调用一次 callback（参数为已处理行数），None 关闭
+    pub fn set_progress(&mut self, progress: Option<(usize, ProgressCallback)>) {
+        self.progress = progress.map(|(every, callback)| ProgressReporter::new(every, callback));
+    }
+
+    // 注册自定义标量函数，可在 SELECT 的结果列中调用（函数名不区分大小写，不能覆盖内置函数）
+    pub fn register_function(&mut self, name: &str, func: ScalarFunction) -> Result<(), String> {
+        if projection::BUILTIN_FUNCTIONS.contains(&name.to_uppercase().as_str()) {
+            return Err(format!("Cannot override built-in function: {}", name));
+        }
+        self.functions.register(name, func);
+        Ok(())
+    }
+
+    // 数据库文件路径
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().unwrap_or(Path::new(DEFAULT_DB_PATH))
+    }
+
+    // 修改 save 写入的文件路径
+    pub fn set_path(&mut self, path: impl Into<PathBuf>) {
+        self.path = Some(path.into());
+    }
+
+    // 按名称查找表（不区分大小写）
+    pub fn table(&self, name: &str) -> Option<&Table> {
+        let normalized_name = name.trim().to_lowercase();
+        self.tables.iter().find(|t| t.name.to_lowercase() == normalized_name)
+    }
+
+    // 遍历所有表
+    pub fn tables_iter(&self) -> impl Iterator<Item = &Table> {
+        self.tables.iter()
+    }
+
+    // 表是否存在（不区分大小写）
+    pub fn table_exists(&self, name: &str) -> bool {
+        self.table(name).is_some()
+    }
+
+    // 表的行数，表不存在时返回 None
+    pub fn row_count(&self, name: &str) -> Option<usize> {
+        self.table(name).map(|t| t.data.len())
+    }
+
+    // 逐行遍历表数据而不复制，表不存在时返回 None
+    pub fn scan(&self, table_name: &str) -> Option<impl Iterator<Item = &[Value]>> {
+        self.table(table_name).map(|t| t.data.iter().map(|row| row.as_slice()))
+    }
+
+    // 返回表的列定义（名称、类型、主键、非空），表不存在时返回 None
+    pub fn columns(&self, table_name: &str) -> Option<Vec<Column>> {
+        self.table(table_name).map(|t| t.columns.clone())
+    }
+
+    // 按定义顺序返回主键列名（复合主键有多列，没有主键时为空），表不存在时返回 None
+    pub fn primary_key(&self, table_name: &str) -> Option<Vec<String>> {
+        self.table(table_name).map(|t| {
+            t.columns.iter().filter(|c| c.is_primary).map(|c| c.name.clone()).collect()
+        })
+    }
+
+    // 统计满足条件的行数：条件只编译一次，且不构建结果行
+    pub fn count_where(&self, table_name: &str, condition: &str) -> Result<usize, DbError> {
+        let table = self.table(table_name)
+            .ok_or_else(|| DbError::TableNotFound(table_name.to_string()))?;
+        let filter_fn = self.row_filter(table, Some(condition)).map_err(DbError::Parse)?;
+        Ok(table.data.iter().filter(|row| filter_fn(row)).count())
+    }
+
+    // 从头重建主键索引并检查数据完整性，同时释放批量删除后多余的存储空间
+    pub fn optimize_table(&mut self, table_name: &str) -> Result<OptimizeStats, String> {
+        let table = self.tables.iter_mut()
+            .find(|t| t.name.eq_ignore_ascii_case(table_name.trim()))
+            .ok_or(format!("Table '{}' not found", table_name))?;
+
+        let mut index = std::collections::HashSet::new();
+        if let Some(pk_index) = table.columns.iter().position(|c| c.is_primary) {
+            for row in &table.data {
+                let key = &row[pk_index];
+                if key.is_null() {
+                    return Err(format!("Primary key '{}' cannot be null in table '{}'", table.columns[pk_index].name, table.name));
+                }
+                if !index.insert(key) {
+                    return Err(format!("Duplicate entry '{}' for key 'PRIMARY' in table '{}'", key, table.name));
+                }
+            }
         }
+        let index_entries = index.len();
+
+        table.data.shrink_to_fit();
+        Ok(OptimizeStats {
+            table: table.name.clone(),
+            rows: table.data.len(),
+            index_entries,
+        })
+    }
+
+    // 扫描一次表数据，统计每列的 NULL 数与不同值数量
+    pub fn analyze(&self, table_name: &str) -> Result<Vec<ColumnStats>, String> {
+        let table = self.table(table_name)
+            .ok_or(format!("Table '{}' not found", table_name))?;
+
+        let mut nulls = vec![0; table.columns.len()];
+        let mut distinct: Vec<std::collections::HashSet<&Value>> =
+            vec![std::collections::HashSet::new(); table.columns.len()];
+
+        for row in &table.data {
+            for (i, value) in row.iter().enumerate() {
+                if value.is_null() {
+                    nulls[i] += 1;
+                } else {
+                    distinct[i].insert(value);
+                }
+            }
+        }
+
+        Ok(table.columns.iter().enumerate().map(|(i, col)| ColumnStats {
+            column: col.name.clone(),
+            total: table.data.len(),
+            nulls: nulls[i],
+            distinct: distinct[i].len(),
+        }).collect())
+    }
+
+    // 一次扫描得到 INT 列的最小值、最大值、平均值和 NULL 数量
+    pub fn column_stats(&self, table_name: &str, column: &str) -> Result<NumericStats, DbError> {
+        let table = self.table(table_name)
+            .ok_or_else(|| DbError::TableNotFound(table_name.to_string()))?;
+        let idx = table.column_index(column)
+            .ok_or_else(|| DbError::Execution(format!("Column '{}' not found", column)))?;
+        let col = &table.columns[idx];
+        if !matches!(col.data_type, DataType::Int(_)) {
+            return Err(DbError::Execution(format!("Column '{}' is not numeric", col.name)));
+        }
+
+        let mut stats = NumericStats { column: col.name.clone(), count: 0, nulls: 0, min: None, max: None, avg: None };
+        let mut sum: i128 = 0;
+        for row in &table.data {
+            match row[idx] {
+                Value::Int(n) => {
+                    stats.count += 1;
+                    sum += n as i128;
+                    stats.min = Some(stats.min.map_or(n, |m| m.min(n)));
+                    stats.max = Some(stats.max.map_or(n, |m| m.max(n)));
+                }
+                _ => stats.nulls += 1,
+            }
+        }
+        if stats.count > 0 {
+            stats.avg = Some(sum as f64 / stats.count as f64);
+        }
+        Ok(stats)
     }
 
     // 创建表方法
@@ -60,6 +447,7 @@ impl Database {
         name: &str,
         columns: Vec<(&str, DataType, bool, bool)>, // (列名, 类型, 是否主键, 是否非空)
     )-> Result<(), String>{
+        self.check_writable()?;
 
         let normalized_name = name.trim().to_lowercase();
         
@@ -68,6 +456,20 @@ impl Database {
         if exists {
             return Err(format!("[REJECTED] Table '{}' exists", normalized_name)); // 确保此返回不可跳过
         }
+        // 表名可以带一级模式（sales.orders），不同模式下的同名表互不冲突
+        let (schema, table) = split_schema(name.trim());
+        if table.is_empty() || table.contains('.') || schema.is_some_and(str::is_empty) {
+            return Err(format!("Invalid table name '{}'", name.trim()));
+        }
+        if columns.is_empty() {
+            return Err(format!("Table '{}' must have at least one column", name.trim()));
+        }
+        // 列名不区分大小写，不能重复
+        for (i, (col, ..)) in columns.iter().enumerate() {
+            if columns[..i].iter().any(|(prev, ..)| prev.eq_ignore_ascii_case(col)) {
+                return Err(format!("Duplicate column name '{}'", col));
+            }
+        }
         self.tables.push(Table {
             name: name.to_string(),
             columns: columns
@@ -77,10 +479,45 @@ impl Database {
                     data_type,
                     is_primary,
                     not_null,
+                    default: None,
+                    comment: None,
                 })
                 .collect(),
             data: Vec::new(),
         });
+        self.parse_cache.clear();
+        self.dropped = false;
+        Ok(())
+    }
+
+    // 设置列的默认值（None 表示省略时为 NULL）
+    pub fn set_column_default(&mut self, table_name: &str, column: &str, default: Option<&str>) -> Result<(), String> {
+        self.check_writable()?;
+        let table = self.tables.iter_mut()
+            .find(|t| t.name.eq_ignore_ascii_case(table_name))
+            .ok_or(format!("Table '{}' not found", table_name))?;
+        let idx = table.column_index(column)
+            .ok_or(format!("Column '{}' not found", column))?;
+
+        if let Some(value) = default {
+            let violations = Self::row_violations(std::slice::from_ref(&table.columns[idx]), &[value]);
+            if !violations.is_empty() {
+                return Err(format!("Invalid default value: {}", violations.join("; ")));
+            }
+        }
+        table.columns[idx].default = default.map(String::from);
+        Ok(())
+    }
+
+    // 设置或清除列注释（CREATE TABLE 中的 COMMENT '...'）
+    pub fn set_column_comment(&mut self, table_name: &str, column: &str, comment: Option<&str>) -> Result<(), String> {
+        self.check_writable()?;
+        let table = self.tables.iter_mut()
+            .find(|t| t.name.eq_ignore_ascii_case(table_name))
+            .ok_or(format!("Table '{}' not found", table_name))?;
+        let idx = table.column_index(column)
+            .ok_or(format!("Column '{}' not found", column))?;
+        table.columns[idx].comment = comment.map(String::from);
         Ok(())
     }
 
@@ -90,74 +527,95 @@ impl Database {
         table_name: &str,
         columns: Option<Vec<String>>,
         values: Vec<Vec<&str>>,
-    ) -> Result<usize, String> {
+    ) -> Result<usize, DbError> {
+        self.check_writable()?;
         self.take_snapshot(); // 在执行前保存快照
+        self.insert_rows(table_name, columns, values)
+    }
+
+    // 插入但不保存快照，供逐行插入、整体只需一个快照的调用方使用（如 .load）
+    pub(crate) fn insert_rows(
+        &mut self,
+        table_name: &str,
+        columns: Option<Vec<String>>,
+        values: Vec<Vec<&str>>,
+    ) -> Result<usize, DbError> {
+        self.check_writable()?;
+        let trim_varchar = self.trim_varchar;
 
         let table = self.tables.iter_mut()
             .find(|t| t.name == table_name)
-            .ok_or("Table not found")?;
+            .ok_or_else(|| DbError::Execution("Table not found".into()))?;
 
         let mut inserted_rows = 0;
 
         for row_values in values {
             // 处理部分插入
             let full_row_values = if let Some(col_names) = &columns {
-                // 创建完整行数据，未指定的列设为空字符串
-                let mut full_row = vec![""; table.columns.len()];
+                // 创建完整行数据，未指定的列使用默认值，没有默认值时为 NULL
+                let mut full_row: Vec<&str> = table.columns.iter()
+                    .map(|c| c.default.as_deref().unwrap_or(""))
+                    .collect();
                 
                 // 检查列名是否匹配
                 if col_names.len() != row_values.len() {
-                    return Err("Column count mismatch in INSERT statement".into());
+                    return Err(DbError::Execution("Column count mismatch in INSERT statement".into()));
                 }
                 
+                let mut assigned = vec![false; table.columns.len()];
                 for (i, col_name) in col_names.iter().enumerate() {
-                    let col_index = table.columns.iter()
-                        .position(|c| &c.name == col_name)
+                    let col_index = table.column_index(col_name)
                         .ok_or(format!("Column '{}' not found", col_name))?;
-                    
+                    // 同一列不能出现两次（列名不区分大小写）
+                    if std::mem::replace(&mut assigned[col_index], true) {
+                        return Err(format!("Column '{}' specified more than once", col_name).into());
+                    }
+
                     full_row[col_index] = row_values[i];
                 }
                 
                 full_row
             } else {
-                // 全列插入
-                if row_values.len() != table.columns.len() {
-                    return Err("Column count mismatch".into());
+                // 全列插入：省略的末尾列按 DEFAULT 处理（默认值或 NULL，NOT NULL 列随后报错）
+                if row_values.is_empty() || row_values.len() > table.columns.len() {
+                    return Err(DbError::Execution("Column count mismatch".into()));
                 }
-                row_values
+                let mut full_row = row_values;
+                full_row.resize(table.columns.len(), DEFAULT_VALUE);
+                full_row
             };
 
-            // 检查NOT NULL约束和主键
-            for (i, (value, column)) in full_row_values.iter().zip(&table.columns).enumerate() {
-                let is_null = value.trim().is_empty() || value.trim().eq_ignore_ascii_case("null");
-                
-                if column.not_null && is_null {
-                    return Err(format!("Column '{}' cannot be null", column.name));
-                }
-                
-                if column.is_primary && is_null {
-                    return Err(format!("Primary key '{}' cannot be null", column.name));
-                }
+            // DEFAULT 关键字替换为列默认值，没有默认值时为 NULL；INT 值去掉首尾空白，
+            // VARCHAR 只在开启 trim_varchar 时去掉，否则原样保存
+            let full_row_values: Vec<&str> = full_row_values.into_iter()
+                .zip(&table.columns)
+                .map(|(value, col)| match col.data_type {
+                    _ if value == DEFAULT_VALUE => col.default.as_deref().unwrap_or(""),
+                    DataType::Int(_) => value.trim(),
+                    DataType::Varchar(_) if trim_varchar => value.trim(),
+                    DataType::Varchar(_) => value,
+                })
+                .collect();
+
+            // 检查NOT NULL、主键、类型和长度约束，一次报告所有违规
+            let violations = Self::row_violations(&table.columns, &full_row_values);
+            if !violations.is_empty() {
+                return Err(DbError::Violations(violations));
             }
 
+            // NULL 关键字已由解析器转换为空字符串，字符串 'NULL' 原样保存
+            let row: Vec<Value> = full_row_values.iter().zip(&table.columns)
+                .map(|(raw, col)| Value::parse(raw, &col.data_type))
+                .collect::<Result<_, _>>()?;
+
             // 主键唯一性检查
             if let Some(pk_index) = table.columns.iter().position(|c| c.is_primary) {
-                let pk_value = full_row_values[pk_index];
-                if !pk_value.trim().is_empty() && !pk_value.trim().eq_ignore_ascii_case("null") {
-                    if table.data.iter().any(|row| row[pk_index] == pk_value) {
-                        return Err(format!("Duplicate entry '{}' for key 'PRIMARY'", pk_value));
-                    }
+                let pk_value = &row[pk_index];
+                if !pk_value.is_null() && table.data.iter().any(|r| &r[pk_index] == pk_value) {
+                    return Err(format!("Duplicate entry '{}' for key 'PRIMARY'", pk_value).into());
                 }
             }
 
-            let row: Vec<String> = full_row_values.iter().map(|s| {
-                if s.trim().eq_ignore_ascii_case("null") {
-                    String::new()
-                } else {
-                    s.to_string()
-                }
-            }).collect();
-            
             table.data.push(row);
             inserted_rows += 1;
         }
@@ -165,19 +623,83 @@ impl Database {
         Ok(inserted_rows)
     }
 
+    // INSERT ... RETURNING：插入后按 columns 投影新插入的行（已补上默认值）
+    pub fn insert_returning(
+        &mut self,
+        table_name: &str,
+        columns: Option<Vec<String>>,
+        values: Vec<Vec<&str>>,
+        returning: Vec<&str>,
+    ) -> Result<Vec<Vec<String>>, DbError> {
+        let count = self.insert(table_name, columns, values)?;
+        let table = self.table(table_name)
+            .ok_or_else(|| DbError::Execution("Table not found".into()))?;
+        let inserted = Table {
+            name: table.name.clone(),
+            columns: table.columns.clone(),
+            data: table.data[table.data.len() - count..].to_vec(),
+        };
+        Ok(self.select_from(&inserted, returning, None, None, None, None)?)
+    }
+
+    // 收集一行数据违反的所有约束
+    fn row_violations(columns: &[Column], row: &[&str]) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        for (value, column) in row.iter().zip(columns) {
+            let is_null = value.trim().is_empty();
+
+            if is_null {
+                if column.not_null {
+                    violations.push(format!("Column '{}' cannot be null", column.name));
+                } else if column.is_primary {
+                    violations.push(format!("Primary key '{}' cannot be null", column.name));
+                }
+                continue;
+            }
+
+            match &column.data_type {
+                DataType::Int(_) if value.trim().parse::<i32>().is_err() => {
+                    violations.push(format!("Value '{}' is not INT for column '{}'", value, column.name));
+                }
+                // VARCHAR(n) 的长度按字符数计算，而不是字节数
+                DataType::Varchar(max_len) if value.chars().count() > *max_len as usize => {
+                    violations.push(format!("Value too long for column '{}' (max {})", column.name, max_len));
+                }
+                _ => {}
+            }
+        }
+
+        violations
+    }
+
     pub fn update(
         &mut self,
         table_name: &str,
         set: Vec<(String, String)>,
         condition: Option<&str>,
     ) -> Result<usize, String> {
+        self.update_with_alias(table_name, None, set, condition)
+    }
+
+    // UPDATE t alias SET ... WHERE ...：条件和 SET 表达式中的列可以用别名限定
+    pub fn update_with_alias(
+        &mut self,
+        table_name: &str,
+        alias: Option<&str>,
+        set: Vec<(String, String)>,
+        condition: Option<&str>,
+    ) -> Result<usize, String> {
+        self.check_writable()?;
         self.take_snapshot(); // 在执行前保存快照
+        let trim_varchar = self.trim_varchar;
 
-        // 1. 获取表的可变引用
-        let table = self.tables
-            .iter_mut()
-            .find(|t| t.name == table_name)
+        // 1. 获取表（条件中的子查询要读取其他表，写回时再取可变引用）
+        let table_index = self.tables
+            .iter()
+            .position(|t| t.name == table_name)
             .ok_or(format!("Table '{}' not found", table_name))?;
+        let table = &self.tables[table_index];
 
         // 2. 提前收集所有需要的列信息 (无需修改)
         let column_names: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
@@ -185,156 +707,334 @@ impl Database {
         let not_null_flags: Vec<bool> = table.columns.iter().map(|c| c.not_null).collect();
         let is_primary_flags: Vec<bool> = table.columns.iter().map(|c| c.is_primary).collect();
 
-        // 3. 创建列名到索引的映射 (修改为使用 String)
+        // 3. 创建列名到索引的映射（列名统一转为小写，不区分大小写）
         let column_map: std::collections::HashMap<String, usize> = column_names
             .iter()
             .enumerate()
-            .map(|(idx, name)| (name.clone(), idx))
+            .map(|(idx, name)| (name.to_lowercase(), idx))
             .collect();
 
-        // 4. 检查主键唯一性 (修改为使用 String)
-        for (col_name, new_value) in &set {
-            if let Some(idx) = column_map.get(col_name) {
-                if is_primary_flags[*idx] {
-                    if table.data.iter().any(|row| &row[*idx] == new_value) {
-                        return Err(format!("Duplicate entry '{}' for key 'PRIMARY'", new_value));
-                    }
-                }
+        // SET 右侧为表达式时先编译
+        let aliased = alias.map(|a| table.aliased(a));
+        let scope = aliased.as_ref().unwrap_or(table);
+        let exprs: Vec<Option<ProjectionExpr>> = set.iter().map(|(_, value)| {
+            match value.strip_prefix(SET_EXPRESSION_PREFIX) {
+                Some(expr) => ProjectionExpr::compile_with(expr, scope, &self.functions).map(Some),
+                None => Ok(None),
             }
-        }
-
-        // 5. 过滤函数 (无需修改)
-        let filter_fn: Box<dyn Fn(&[String]) -> bool> = if let Some(cond) = condition {
-            let columns = table.columns.clone();
-            Box::new(move |row: &[String]| {
-                let temp_table = Table {
-                    name: String::new(),
-                    columns: columns.clone(),
-                    data: vec![],
-                };
-                match Self::parse_condition(cond, &temp_table) {
-                    Ok(filter) => filter(row),
-                    Err(_) => false,
-                }
-            })
-        } else {
-            Box::new(|_| true)
-        };
+        }).collect::<Result<_, _>>()?;
 
-        // 6. 执行更新 (修改为使用 String)
-        let mut affected_rows = 0;
-        for row in &mut table.data {
-            if filter_fn(row) {
-                affected_rows += 1;
-                for (col_name, new_value) in &set {
-                    if let Some(idx) = column_map.get(col_name) {
-                        // 类型检查
-                        match &column_types[*idx] {
-                            DataType::Int(_) if new_value.parse::<i32>().is_err() => {
-                                return Err(format!("Value '{}' is not INT for column '{}'", 
-                                    new_value, col_name));
-                            },
-                            DataType::Varchar(max_len) if new_value.len() > *max_len as usize => {
-                                return Err(format!("Value too long for column '{}' (max {})", 
-                                    col_name, max_len));
-                            },
-                            _ => {}
-                        }
+        // 4. 先编译条件，整条语句复用；条件无效时报错而不是静默地不更新任何行
+        // 条件中的列可以用表名（或别名）限定
+        let filter_fn = self.row_filter(scope, condition)?;
 
-                        // 非空检查
-                        if not_null_flags[*idx] && new_value.is_empty() {
+        // 5. 先计算每个匹配行更新后的值，全部通过检查后再写回
+        let mut updated: Vec<(usize, Vec<Value>)> = Vec::new();
+        for (i, row) in table.data.iter().enumerate() {
+            if let Some(progress) = self.progress.as_mut() {
+                progress.tick(i + 1);
+            }
+            if !filter_fn(row) {
+                continue;
+            }
+            // 所有表达式都按更新前的行求值，SET a = b, b = a 会交换两列
+            let mut new_row = row.clone();
+            for ((col_name, raw_value), expr) in set.iter().zip(&exprs) {
+                let evaluated;
+                let new_value = match expr {
+                    Some(expr) => {
+                        evaluated = expr.eval(row)?.to_string();
+                        &evaluated
+                    }
+                    None => raw_value,
+                };
+                if let Some(idx) = column_map.get(&col_name.to_lowercase()) {
+                    let new_value = match column_types[*idx] {
+                        DataType::Varchar(_) if trim_varchar => new_value.trim(),
+                        _ => new_value.as_str(),
+                    };
+                    // SET col = NULL（空字符串）：NOT NULL 列和主键列拒绝，其余列清空
+                    if new_value.is_empty() {
+                        if not_null_flags[*idx] || is_primary_flags[*idx] {
                             return Err(format!("Column '{}' cannot be null", col_name));
                         }
+                        new_row[*idx] = Value::Null;
+                        continue;
+                    }
 
-                        row[*idx] = new_value.clone();
+                    // 类型检查
+                    match &column_types[*idx] {
+                        DataType::Int(_) if new_value.parse::<i32>().is_err() => {
+                            return Err(format!("Value '{}' is not INT for column '{}'", 
+                                new_value, col_name));
+                        },
+                        DataType::Varchar(max_len) if new_value.chars().count() > *max_len as usize => {
+                            return Err(format!("Value too long for column '{}' (max {})", 
+                                col_name, max_len));
+                        },
+                        _ => {}
                     }
+
+                    new_row[*idx] = Value::parse(new_value, &column_types[*idx])?;
                 }
             }
+            updated.push((i, new_row));
+        }
+
+        // 6. 检查主键唯一性：新值不能与未更新的行重复，也不能在本次更新的行之间重复
+        let assigned: Vec<usize> = set.iter()
+            .filter_map(|(col_name, _)| column_map.get(&col_name.to_lowercase()).copied())
+            .collect();
+        let updated_rows: std::collections::HashSet<usize> = updated.iter().map(|(i, _)| *i).collect();
+        for idx in (0..column_names.len()).filter(|&idx| is_primary_flags[idx] && assigned.contains(&idx)) {
+            let mut seen: std::collections::HashSet<&Value> = table.data.iter()
+                .enumerate()
+                .filter(|(i, _)| !updated_rows.contains(i))
+                .map(|(_, row)| &row[idx])
+                .collect();
+            for (_, new_row) in &updated {
+                if !seen.insert(&new_row[idx]) {
+                    return Err(format!("Duplicate entry '{}' for key 'PRIMARY'", new_row[idx]));
+                }
+            }
+        }
+
+        let affected_rows = updated.len();
+        let table = &mut self.tables[table_index];
+        for (i, new_row) in updated {
+            table.data[i] = new_row;
         }
 
         Ok(affected_rows)
     }
 
-    pub fn delete(&mut self,table_name: &str,condition: Option<&str>,) -> Result<usize, String> {
+    pub fn delete(&mut self,table_name: &str,condition: Option<&str>,) -> Result<usize, DbError> {
+        self.delete_with_alias(table_name, None, condition)
+    }
+
+    // DELETE FROM t alias WHERE ...：条件中的列可以用别名限定
+    pub fn delete_with_alias(
+        &mut self,
+        table_name: &str,
+        alias: Option<&str>,
+        condition: Option<&str>,
+    ) -> Result<usize, DbError> {
+        self.check_writable()?;
         self.take_snapshot(); // 在执行前保存快照
 
-        // 1. 获取表的可变引用
-        let table = self.tables
-            .iter_mut()
-            .find(|t| t.name == table_name)
-            .ok_or(format!("Table '{}' not found", table_name))?;
+        // 1. 获取表（条件中的子查询要读取其他表，删除时再取可变引用）
+        let table_index = self.tables
+            .iter()
+            .position(|t| t.name == table_name)
+            .ok_or_else(|| DbError::TableNotFound(table_name.to_string()))?;
 
-        // 2. 提前复制所需的列信息
-        let columns = table.columns.clone();
-
-        // 3. 创建过滤闭包
-        let filter_fn: Box<dyn Fn(&[String]) -> bool> = if let Some(cond) = condition {
-            // 使用提前复制的列信息
-            Box::new(move |row: &[String]| {
-                let local_table = Table {
-                    name: String::new(),
-                    columns: columns.clone(),
-                    data: vec![],
-                };
-                match Self::parse_condition(cond, &local_table) {
-                    Ok(filter) => filter(row),
-                    Err(_) => false, // 解析失败时不匹配任何行
-                }
-            })
-        } else {
-            Box::new(|_| true) // 无条件时匹配所有行
-        };
+        // 2. 先编译条件（展开子查询），条件无效时报错而不是静默地不删除任何行；无条件时匹配所有行
+        let table = &self.tables[table_index];
+        let aliased = alias.map(|a| table.aliased(a));
+        let filter_fn = self.row_filter(aliased.as_ref().unwrap_or(table), condition).map_err(DbError::Parse)?;
 
-        // 4. 执行删除操作
+        // 3. 执行删除操作
+        let table = &mut self.tables[table_index];
         let original_len = table.data.len();
-        table.data.retain(|row| !filter_fn(row));
+        let mut processed = 0;
+        let progress = &mut self.progress;
+        table.data.retain(|row| {
+            processed += 1;
+            if let Some(progress) = progress.as_mut() {
+                progress.tick(processed);
+            }
+            !filter_fn(row)
+        });
         let affected_rows = original_len - table.data.len();
 
         Ok(affected_rows)
     }
 
+    // 先写入临时文件再重命名覆盖，序列化或写入失败时原文件保持不变
     pub fn save(&self) -> Result<(), String> {
-        // 创建data目录（如果不存在）
-        fs::create_dir_all("data").map_err(|e| e.to_string())?;
+        self.check_writable()?;
+        // 数据库刚被 DROP 时不重建文件（退出和自动保存都会走到这里）
+        if self.dropped {
+            return Ok(());
+        }
+        let path = self.path();
+
+        // 创建数据目录（如果不存在）
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
 
-        // 序列化为JSON并保存
-        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
-        fs::write("data/db.json", json).map_err(|e| e.to_string())?;
+        let json = if self.compact_json {
+            serde_json::to_string(self)
+        } else {
+            serde_json::to_string_pretty(self)
+        }.map_err(|e| e.to_string())?;
+        let mut tmp_name = path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_name);
+        if let Err(e) = fs::write(&tmp_path, json).and_then(|_| fs::rename(&tmp_path, path)) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e.to_string());
+        }
 
         Ok(())
     }
 
     pub fn load() -> Result<Self, String> {
+        // 先获取文件锁，防止另一个进程同时打开并覆盖数据
+        let lock = DbLock::acquire(Path::new(DEFAULT_DB_PATH))?;
+
         // 检查文件是否存在
-        if !Path::new("data/db.json").exists() {
-            return Ok(Database::new());
-        }
+        let mut db = if !Path::new(DEFAULT_DB_PATH).exists() {
+            Database::new()
+        } else {
+            // 读取并反序列化
+            let json = fs::read_to_string(DEFAULT_DB_PATH).map_err(|e| e.to_string())?;
+            let mut db: Database = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            db.retype_cells();
+            db
+        };
 
-        // 读取并反序列化
-        let json = fs::read_to_string("data/db.json").map_err(|e| e.to_string())?;
-        serde_json::from_str(&json).map_err(|e| e.to_string())
+        db.lock = Some(lock);
+        Ok(db)
     }
 
-    pub fn load_with_history(history: &mut CommandHistory) -> Result<Self, String> {
-        if !Path::new("data/db.json").exists() {
-            return Ok(Database::new());
-        }
+    pub fn load_with_history(history: &mut CommandHistory) -> Result<Self, DbError> {
+        Self::load_with_history_from(
+            Path::new(DEFAULT_DB_PATH),
+            Path::new(DEFAULT_HISTORY_PATH),
+            history,
+        )
+    }
+
+    // 严格加载：单元格与列类型不符时拒绝打开（适合检查手工编辑过的 db.json）
+    pub fn load_strict_with_history(history: &mut CommandHistory) -> Result<Self, DbError> {
+        Self::load_strict_with_history_from(
+            Path::new(DEFAULT_DB_PATH),
+            Path::new(DEFAULT_HISTORY_PATH),
+            history,
+        )
+    }
+
+    // 从指定路径加载数据库和命令历史，任一文件不存在时按空处理
+    pub fn load_with_history_from(
+        db_path: &Path,
+        history_path: &Path,
+        history: &mut CommandHistory,
+    ) -> Result<Self, DbError> {
+        Self::open_with_history(db_path, history_path, history, false)
+    }
+
+    pub fn load_strict_with_history_from(
+        db_path: &Path,
+        history_path: &Path,
+        history: &mut CommandHistory,
+    ) -> Result<Self, DbError> {
+        Self::open_with_history(db_path, history_path, history, true)
+    }
 
-        let json = fs::read_to_string("data/db.json")
-            .map_err(|e| format!("Failed to read db.json: {}", e))?;
+    fn open_with_history(
+        db_path: &Path,
+        history_path: &Path,
+        history: &mut CommandHistory,
+        strict: bool,
+    ) -> Result<Self, DbError> {
+        let lock = DbLock::acquire(db_path)?;
 
-        let mut db: Database = serde_json::from_str(&json)
-            .map_err(|e| format!("Failed to parse db.json: {}", e))?;
+        let mut db = if db_path.exists() {
+            Self::read_file(db_path, strict)?
+        } else {
+            Database::new()
+        };
 
-        // 加载历史记录
+        // 兼容旧版本：历史记录曾嵌在 db.json 中
         for cmd in db.command_history.drain(..) {
             history.add(cmd.as_str());
         }
 
+        if history_path.exists() {
+            history.load_from(history_path).map_err(DbError::Io)?;
+        }
+
+        db.path = Some(db_path.to_path_buf());
+        db.lock = Some(lock);
         Ok(db)
     }
 
+    // 以只读方式打开默认数据库；不获取文件锁，可与正在运行的实例同时查看
+    pub fn load_readonly() -> Result<Self, DbError> {
+        Self::load_readonly_from(Path::new(DEFAULT_DB_PATH))
+    }
+
+    pub fn load_readonly_from(db_path: &Path) -> Result<Self, DbError> {
+        let mut db = if db_path.exists() {
+            Self::read_file(db_path, false)?
+        } else {
+            Database::new()
+        };
+
+        db.path = Some(db_path.to_path_buf());
+        db.read_only = true;
+        Ok(db)
+    }
+
+    // 文件中的单元格都以文本保存，加载后按列类型转换；
+    // 无法转换的值（例如手工改成 "abc" 的 INT）保留为文本，由 validate() 报告
+    fn retype_cells(&mut self) {
+        for table in &mut self.tables {
+            for row in &mut table.data {
+                for (value, col) in row.iter_mut().zip(&table.columns) {
+                    if let Ok(typed) = value.clone().coerce(&col.data_type) {
+                        *value = typed;
+                    }
+                }
+            }
+        }
+    }
+
+    // 读取并反序列化数据库文件；strict 时任何单元格与列类型不符都拒绝加载，并一次列出所有问题
+    fn read_file(db_path: &Path, strict: bool) -> Result<Self, DbError> {
+        let json = fs::read_to_string(db_path)
+            .map_err(|e| DbError::Io(format!("Failed to read {}: {}", db_path.display(), e)))?;
+        let mut db = serde_json::from_str::<Database>(&json)
+            .map_err(|e| DbError::Parse(format!("{}: {}", db_path.display(), e)))?;
+        db.retype_cells();
+        if strict {
+            let mismatches: Vec<String> = db.type_mismatches().iter().map(|e| e.to_string()).collect();
+            if !mismatches.is_empty() {
+                return Err(DbError::Parse(format!("{}: {}", db_path.display(), mismatches.join("; "))));
+            }
+        }
+        Ok(db)
+    }
+
+    // 清空所有表（仅内存，需要调用 save 才会写盘）
+    pub fn clear_all(&mut self) -> Result<(), DbError> {
+        self.check_writable()?;
+        self.take_snapshot();
+        self.tables.clear();
+        self.parse_cache.clear();
+        Ok(())
+    }
+
+    // DROP DATABASE：清空所有表并删除数据库文件（与 clear_all 不同，文件不保留）；
+    // 文件不存在时不报错，快照一并丢弃，无法 UNDO
+    pub fn drop_all(&mut self) -> Result<(), DbError> {
+        self.check_writable()?;
+        match fs::remove_file(self.path()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(DbError::Io(format!("Failed to delete {}: {}", self.path().display(), e))),
+        }
+        self.tables.clear();
+        self.parse_cache.clear();
+        self.snapshots.clear();
+        self.dropped = true;
+        Ok(())
+    }
+
     pub fn drop_tables(&mut self, table_names: &[String], if_exists: bool) -> Result<usize, String> {
+        self.check_writable()?;
         let original_count = self.tables.len();
         
         // 只有 if_exists=false 时才检查存在性
@@ -350,7 +1050,10 @@ impl Database {
         self.tables.retain(|table| !table_names.contains(&table.name));
         
         let dropped_count = original_count - self.tables.len();
-        
+        if dropped_count > 0 {
+            self.parse_cache.clear();
+        }
+
         // 如果实际删除数量为0且指定了必须存在，报错
         if dropped_count == 0 && !if_exists {
             return Err("No tables were dropped".into());
@@ -364,64 +1067,95 @@ impl Database {
         table_name: &str,
         columns: Vec<&str>,
         condition: Option<&str>,
-        order_by: Option<Vec<(&str, bool)>>  // (列名, 是否降序)
+        order_by: Option<Vec<(&str, bool)>>,  // (列名, 是否降序)
+        limit: Option<usize>,                  // 最多返回的行数
+        offset: Option<usize>,                 // 跳过的行数
     ) -> Result<Vec<Vec<String>>, String> {
         let table = self.tables
             .iter()
             .find(|t| t.name == table_name)
             .ok_or("Table not found")?;
+        self.select_from(table, columns, condition, order_by, limit, offset)
+    }
 
-        // 获取结果列索引
-        let column_indices: Vec<usize> = if columns == ["*"] {
-            (0..table.columns.len()).collect()
-        } else {
-            columns.iter().map(|col| {
-                table.columns.iter().position(|c| &c.name == col)
-                    .ok_or(format!("Column '{}' not found", col))
-            }).collect::<Result<_, _>>()?
-        };
+    // 依次连接各表，生成列名带限定符（别名或表名）的临时表
+    pub fn joined_table(&self, base: &str, alias: Option<&str>, joins: &[Join]) -> Result<Table, String> {
+        let first = self.table(base)
+            .ok_or(format!("Table '{}' not found", base))?;
+        let mut result = join::qualify(first, alias.unwrap_or(&first.name));
+
+        for j in joins {
+            let right = self.table(&j.table)
+                .ok_or(format!("Table '{}' not found", j.table))?;
+            let right = join::qualify(right, j.alias.as_deref().unwrap_or(&right.name));
+            result = j.apply(result, right)?;
+        }
 
-        // 统一返回 Box<dyn Fn> 类型
-        let filter_fn: Box<dyn Fn(&[String]) -> bool> = if let Some(cond) = condition {
-            Self::parse_condition(cond, table)?
-        } else {
-            Box::new(|_| true) // 将闭包装箱
-        };
+        Ok(result)
+    }
+
+    // 在给定的表（可以是连接结果）上执行查询
+    pub fn select_from(
+        &self,
+        table: &Table,
+        columns: Vec<&str>,
+        condition: Option<&str>,
+        order_by: Option<Vec<(&str, bool)>>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<Vec<String>>, String> {
+        // 含聚合函数时按单个分组处理
+        if columns.iter().any(|col| group::is_aggregate(col)) {
+            return self.select_grouped_from(table, columns, condition, Vec::new(), order_by, limit, offset);
+        }
+
+        // 编译结果列（普通列或函数表达式），* 在所在位置展开为所有列
+        let mut projections: Vec<ProjectionExpr> = Vec::with_capacity(columns.len());
+        for col in &columns {
+            if *col == "*" {
+                projections.extend((0..table.columns.len()).map(ProjectionExpr::Column));
+                continue;
+            }
+            projections.push(match table.column_index(col) {
+                Some(idx) => ProjectionExpr::Column(idx),
+                None => ProjectionExpr::compile_with(col, table, &self.functions)?,
+            });
+        }
+
+        let filter_fn = self.row_filter(table, condition)?;
 
         // 收集原始行数据（带原始行索引）
-        let mut rows_with_indices: Vec<(usize, &Vec<String>)> = table.data
+        let mut rows_with_indices: Vec<(usize, &Vec<Value>)> = table.data
             .iter()
             .enumerate()
             .filter(|(_, row)| filter_fn(row))
             .collect();
 
         // 处理排序（如果需要）
-        if let Some(cols) = order_by {
-            // 获取排序列元数据
-            let sort_specs: Vec<(usize, &DataType, bool)> = cols.into_iter().map(|(col, desc)| {
-                let col_idx = table.columns.iter()
-                    .position(|c| c.name == col)
+        if let Some(mut cols) = order_by {
+            // ORDER BY RANDOM()：先洗牌，再按其之前的排序列做稳定排序
+            if let Some(pos) = cols.iter().position(|(col, _)| *col == RANDOM_ORDER_KEY) {
+                let mut rng = match self.random_seed {
+                    Some(seed) => SplitMix64::new(seed),
+                    None => SplitMix64::from_time(),
+                };
+                rng.shuffle(&mut rows_with_indices);
+                cols.truncate(pos);
+            }
+
+            // 获取排序列
+            let sort_specs: Vec<(usize, bool, Option<bool>)> = cols.into_iter().map(|(col, desc)| {
+                let (col, nulls_first) = split_nulls_order(col);
+                let col_idx = table.column_index(col)
                     .ok_or(format!("Sort column '{}' not found", col))?;
-                Ok((col_idx, &table.columns[col_idx].data_type, desc))
+                Ok((col_idx, desc, nulls_first))
             }).collect::<Result<_, String>>()?;
 
-            // 排序逻辑（使用原始数据）
-            rows_with_indices.sort_by(|(a_idx, _), (b_idx, _)| {
-                let a_row = &table.data[*a_idx];
-                let b_row = &table.data[*b_idx];
-
-                for (col_idx, data_type, desc) in &sort_specs {
-                    let ordering = match data_type {
-                        DataType::Int(_) => {
-                            a_row[*col_idx].parse::<i32>().unwrap_or(0)
-                                .cmp(&b_row[*col_idx].parse::<i32>().unwrap_or(0))
-                        },
-                        DataType::Varchar(_) => a_row[*col_idx].cmp(&b_row[*col_idx]),
-                    };
-
-                    if *desc {
-                        return ordering.reverse();
-                    } else if ordering != std::cmp::Ordering::Equal {
+            // 按类型化的值排序，NULL 的位置由 NULLS FIRST/LAST 决定；相等时继续比较后面的列
+            rows_with_indices.sort_by(|(_, a_row), (_, b_row)| {
+                for (col_idx, desc, nulls_first) in &sort_specs {
+                    let ordering = value::compare_for_sort(&a_row[*col_idx], &b_row[*col_idx], *desc, *nulls_first);
+                    if ordering != std::cmp::Ordering::Equal {
                         return ordering;
                     }
                 }
@@ -429,20 +1163,44 @@ impl Database {
             });
         }
 
+        // 排序后再取结果窗口（OFFSET/LIMIT）
+        if let Some(skip) = offset {
+            rows_with_indices.drain(..skip.min(rows_with_indices.len()));
+        }
+        if let Some(n) = limit {
+            rows_with_indices.truncate(n);
+        }
+
         // 构建最终结果
         let result = rows_with_indices.into_iter()
             .map(|(_, row)| {
-                column_indices.iter().map(|&i| row[i].clone()).collect()
+                projections.iter().map(|p| p.eval(row).map(|v| v.to_string())).collect::<Result<Vec<_>, _>>()
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         Ok(result)
     }
 
+    // 编译 WHERE 条件（先展开子查询），无条件时匹配所有行
+    fn row_filter(&self, table: &Table, condition: Option<&str>) -> Result<Box<dyn Fn(&[Value]) -> bool>, String> {
+        match condition {
+            Some(cond) => {
+                let cond = self.resolve_subqueries(cond)?;
+                Self::parse_condition(&cond, table)
+            }
+            None => Ok(Box::new(|_| true)), // 将闭包装箱
+        }
+    }
+
     pub fn parse_condition(
         cond: &str,
         table: &Table,
-    ) -> Result<Box<dyn Fn(&[String]) -> bool>, String> {
+    ) -> Result<Box<dyn Fn(&[Value]) -> bool>, String> {
+        // 整个条件被一对括号包住时是分组，去掉括号再解析；
+        // IN (...) 的括号前面总有列名和 IN，不会被当作分组
+        if let Some(inner) = Self::strip_group_parens(cond) {
+            return Self::parse_condition(inner, table);
+        }
         // 首先检查是否包含 AND 关键字（不区分大小写）
         if cond.to_uppercase().contains(" AND ") {
             return Self::parse_and_condition(cond, table);
@@ -453,14 +1211,39 @@ impl Database {
     fn parse_single_condition(
         cond: &str,
         table: &Table,
-    ) -> Result<Box<dyn Fn(&[String]) -> bool>, String> {
+    ) -> Result<Box<dyn Fn(&[Value]) -> bool>, String> {
         // 原有 parse_condition 的实现内容
-        let re = regex::Regex::new(r#"(?:("[^"]*")|('[^']*')|(\S+))"#).unwrap();
-        let parts: Vec<&str> = re.find_iter(cond)
-            .map(|m| m.as_str())
-            .collect();
+        // 引号内的 '' 或 "" 表示转义的引号
+        static TOKEN_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+            regex::Regex::new(r#"(?:("(?:[^"]|"")*")|('(?:[^']|'')*')|(\S+))"#).unwrap()
+        });
+        // 左侧可以是带括号的表达式，例如 CAST(id AS INT)
+        let (left, rest) = Self::split_condition_operand(cond);
+
+        // col [NOT] IN (v1, v2, ...)
+        if let Some((negated, values)) = Self::parse_in_list(rest) {
+            let left = Self::condition_operand(left, table)?;
+            return Ok(Box::new(move |row| {
+                let row_val = left.eval(row).unwrap_or(Value::Null);
+                // NULL 不属于任何列表
+                !row_val.is_null() && values.iter().any(|v| row_val == *v.as_str()) != negated
+            }));
+        }
+
+        // col [NOT] LIKE|ILIKE 'pattern' [ESCAPE 'c']
+        if let Some((negated, pattern)) = Self::parse_like(rest)? {
+            let left = Self::condition_operand(left, table)?;
+            return Ok(Box::new(move |row| {
+                let row_val = left.eval(row).unwrap_or(Value::Null);
+                // NULL 既不匹配 LIKE 也不匹配 NOT LIKE
+                !row_val.is_null() && pattern.matches(&row_val.to_string()) != negated
+            }));
+        }
+
+        let mut parts: Vec<&str> = vec![left];
+        parts.extend(TOKEN_RE.find_iter(rest).map(|m| m.as_str()));
 
-        if parts.len() != 3 && !(parts.len() == 4 && parts[1] == "IS" && (parts[3] == "NULL" || parts[3] == "NOT NULL")) {
+        if parts.len() != 3 && !(parts.len() == 4 && parts[1] == "IS" && parts[2] == "NOT") {
             return Err(format!("Invalid WHERE format. Expected 'column op value', got: {:?}", parts));
         }
 
@@ -474,107 +1257,318 @@ impl Database {
             }
         );
 
-        let val = raw_val.trim_matches(|c| c == '"' || c == '\'').to_string();
-        let col_idx = table.columns.iter()
-            .position(|c| c.name == col)
-            .ok_or(format!("Column '{}' not found in table", col))?;
+        let val = Self::unquote_condition_value(&raw_val);
+        let left = Self::condition_operand(col, table)?;
+
+        // 右侧为未加引号的列名时比较同一行的两列（列名优先于字面量）
+        let right_col = raw_val.trim_matches('`');
+        let is_literal = raw_val.starts_with(['\'', '"']);
+        if let Some(right_idx) = table.column_index(right_col).filter(|_| !is_literal) {
+            let wanted = match op {
+                ">" => std::cmp::Ordering::Greater,
+                "<" => std::cmp::Ordering::Less,
+                "=" => std::cmp::Ordering::Equal,
+                _ => return Err(format!("Unsupported operator: {}", op)),
+            };
+            let is_int = |idx: usize| matches!(table.columns[idx].data_type, DataType::Int(_));
+            let numeric = is_int(right_idx) && matches!(left, ProjectionExpr::Column(idx) if is_int(idx));
+            return Ok(Box::new(Self::column_comparison(left, wanted, right_idx, numeric)));
+        }
+        let value_of = move |row: &[Value]| left.eval(row).unwrap_or(Value::Null);
 
+        // 整数列按数值比较，文本列按字符串比较，NULL 不满足任何比较
         Ok(match op {
             ">" => Box::new(move |row| {
-                let row_val = row[col_idx].trim_matches('"').parse::<i32>().unwrap_or(0);
-                let cond_val = val.parse::<i32>().unwrap_or(0);
-                row_val > cond_val
+                value_of(row).compare_literal(&val) == Some(std::cmp::Ordering::Greater)
             }),
             "<" => Box::new(move |row| {
-                let row_val = row[col_idx].trim_matches('"').parse::<i32>().unwrap_or(0);
-                let cond_val = val.parse::<i32>().unwrap_or(0);
-                row_val < cond_val
+                value_of(row).compare_literal(&val) == Some(std::cmp::Ordering::Less)
             }),
+            // 与 NULL 关键字比较不匹配任何行
+            "=" if raw_val.eq_ignore_ascii_case("NULL") => Box::new(|_| false),
             "=" => Box::new(move |row| {
-                let row_val = row[col_idx].trim_matches('"');
-                row_val == val
+                value_of(row).compare_literal(&val) == Some(std::cmp::Ordering::Equal)
             }),
             "IS" if val == "NULL" => Box::new(move |row| {
-                row[col_idx].trim_matches('"').is_empty()
+                value_of(row).is_null()
             }),
             "IS" if val == "NOT NULL" => Box::new(move |row| {
-                !row[col_idx].trim_matches('"').is_empty()
+                !value_of(row).is_null()
             }),
+            // 布尔以整数保存（非 0 为真），NULL 既不是 TRUE 也不是 FALSE
+            "IS" if val == "TRUE" => Box::new(move |row| value_of(row).truth() == Some(true)),
+            "IS" if val == "FALSE" => Box::new(move |row| value_of(row).truth() == Some(false)),
+            "IS" if val == "NOT TRUE" => Box::new(move |row| value_of(row).truth() != Some(true)),
+            "IS" if val == "NOT FALSE" => Box::new(move |row| value_of(row).truth() != Some(false)),
             _ => return Err(format!("Unsupported operator: {}", op)),
         })
     }
     
-    fn parse_and_condition(
-        cond: &str,
-        table: &Table,
-    ) -> Result<Box<dyn Fn(&[String]) -> bool>, String> {
-        //println!("[DEBUG] Original condition: {}", cond);
-        
-        // 分割条件，处理可能的嵌套情况
-        let mut parts = Vec::new();
-        let mut current_part = String::new();
-        let mut in_quotes = false;
-        let mut paren_depth = 0;
-        let mut chars = cond.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            //println!("[DEBUG] Processing char: '{}', in_quotes: {}, paren_depth: {}, current_part: '{}'", 
-                //c, in_quotes, paren_depth, current_part);
-
-            match c {
-                '"' | '\'' => {
-                    in_quotes = !in_quotes;
-                    current_part.push(c);
+    // 两列比较：都是 Int 时按数值比较，否则按字符串比较；NULL 不满足任何比较
+    fn column_comparison(
+        left: ProjectionExpr,
+        wanted: std::cmp::Ordering,
+        right_idx: usize,
+        numeric: bool,
+    ) -> impl Fn(&[Value]) -> bool {
+        move |row: &[Value]| {
+            let a = left.eval(row).unwrap_or(Value::Null);
+            let b = &row[right_idx];
+            if a.is_null() || b.is_null() {
+                return false;
+            }
+            let ordering = match (&a, b) {
+                (Value::Int(x), Value::Int(y)) if numeric => x.cmp(y),
+                _ => a.to_string().cmp(&b.to_string()),
+            };
+            ordering == wanted
+        }
+    }
+
+    // 条件左侧：列名或带括号的表达式
+    fn condition_operand(col: &str, table: &Table) -> Result<ProjectionExpr, String> {
+        if col.contains('(') {
+            let expr = ProjectionExpr::compile(col, table)?;
+            // 提前对所有行求值，无法转换的值直接报错
+            for row in &table.data {
+                expr.eval(row)?;
+            }
+            return Ok(expr);
+        }
+        // 列名可用 "" 或 `` 引起来（例如保留字 "order"）
+        let col = col.trim_matches(|c| c == '"' || c == '`');
+        let col_idx = table.column_index(col)
+            .ok_or(format!("Column '{}' not found in table", col))?;
+        Ok(ProjectionExpr::Column(col_idx))
+    }
+
+    // 解析 [NOT] IN (...) 的值列表，返回 (是否 NOT, 值)
+    // 条件首尾的括号是同一对时返回括号内的部分（引号内的括号不算），例如
+    // (a = 1 AND b = 2) 返回 a = 1 AND b = 2，而 (a) = 1 和 a IN (1, 2) 返回 None
+    fn strip_group_parens(cond: &str) -> Option<&str> {
+        let cond = cond.trim();
+        let inner = cond.strip_prefix('(')?.strip_suffix(')')?;
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        for c in inner.chars() {
+            match (c, quote) {
+                (q, Some(open)) if q == open => quote = None,
+                (_, Some(_)) => {}
+                ('"' | '\'' | '`', None) => quote = Some(c),
+                ('(', None) => depth += 1,
+                // 开头的括号在末尾之前就已闭合
+                (')', None) if depth == 0 => return None,
+                (')', None) => depth -= 1,
+                _ => {}
+            }
+        }
+        Some(inner.trim())
+    }
+
+    fn parse_in_list(rest: &str) -> Option<(bool, Vec<String>)> {
+        static IN_LIST_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+            regex::Regex::new(r"(?is)^\s*(NOT\s+)?IN\s*\((.*)\)\s*$").unwrap()
+        });
+        let caps = IN_LIST_RE.captures(rest)?;
+        let negated = caps.get(1).is_some();
+
+        // 列表项可以带括号，例如 IN ((1), ('a'))；括号内的逗号不分隔列表项
+        let list_value = |raw: &str| {
+            let mut value = raw.trim();
+            while let Some(inner) = Self::strip_group_parens(value) {
+                value = inner;
+            }
+            Self::unquote_condition_value(value)
+        };
+        let mut values = Vec::new();
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+        let mut depth = 0;
+        for c in caps[2].chars() {
+            match (c, quote) {
+                (q, Some(open)) if q == open => quote = None,
+                (_, Some(_)) => {}
+                ('\'' | '"', None) => quote = Some(c),
+                ('(', None) => depth += 1,
+                (')', None) => depth -= 1,
+                (',', None) if depth == 0 => {
+                    values.push(list_value(&current));
+                    current.clear();
+                    continue;
                 }
-                '(' if !in_quotes => {
-                    paren_depth += 1;
-                    current_part.push(c);
+                _ => {}
+            }
+            current.push(c);
+        }
+        if !current.trim().is_empty() || !values.is_empty() {
+            values.push(list_value(&current));
+        }
+        Some((negated, values))
+    }
+
+    // 解析 [NOT] LIKE|ILIKE 'pattern' [ESCAPE 'c']，未指定 ESCAPE 时以反斜杠转义；ILIKE 忽略大小写
+    fn parse_like(rest: &str) -> Result<Option<(bool, LikePattern)>, String> {
+        static LIKE_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(
+            r"(?is)^\s*(NOT\s+)?(I?)LIKE\s+('(?:[^']|'')*')(?:\s+ESCAPE\s+('(?:[^']|'')*'))?\s*$"
+        ).unwrap());
+        let Some(caps) = LIKE_RE.captures(rest) else {
+            return Ok(None);
+        };
+        let negated = caps.get(1).is_some();
+        let ignore_case = !caps[2].is_empty();
+        let pattern = Self::unquote_condition_value(&caps[3]);
+
+        // ESCAPE '' 表示不使用转义字符
+        let escape = match caps.get(4).map(|m| Self::unquote_condition_value(m.as_str())) {
+            None => Some('\\'),
+            Some(e) if e.is_empty() => None,
+            Some(e) => {
+                let mut chars = e.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => return Err(format!("ESCAPE must be a single character, got '{}'", e)),
                 }
-                ')' if !in_quotes => {
-                    paren_depth -= 1;
-                    current_part.push(c);
+            }
+        };
+        let pattern = LikePattern::compile(&pattern, escape)?;
+        Ok(Some((negated, if ignore_case { pattern.ignoring_case() } else { pattern })))
+    }
+
+    // 执行条件中不相关的子查询，并替换为字面量：
+    // IN (SELECT ...) 替换为值列表，其余位置替换为单个值
+    // 第一个不在引号内的 (SELECT 的位置；直接在原串上按字节扫描并只做 ASCII 大小写比较，
+    // 多字节字符不会让位置错位
+    fn find_subquery(cond: &str) -> Option<usize> {
+        let bytes = cond.as_bytes();
+        let mut quote: Option<u8> = None;
+        for (i, &b) in bytes.iter().enumerate() {
+            match (b, quote) {
+                (q, Some(open)) if q == open => quote = None,
+                (_, Some(_)) => {}
+                (b'\'' | b'"', None) => quote = Some(b),
+                (b'(', None) if bytes.get(i + 1..i + 8).is_some_and(|w| w.eq_ignore_ascii_case(b"SELECT ")) => {
+                    return Some(i);
                 }
-                _ if c.to_ascii_uppercase() == 'A' 
-                    && !in_quotes 
-                    && paren_depth == 0 
-                    && current_part.ends_with(' ') => {
-                    
-                    // 检查是否是完整的AND关键字
-                    let mut and_chars = vec!['A'];
-                    for _ in 0..2 {
-                        if let Some(&next_c) = chars.peek() {
-                            and_chars.push(next_c.to_ascii_uppercase());
-                            chars.next();
-                        }
-                    }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn resolve_subqueries(&self, cond: &str) -> Result<String, String> {
+        let Some(start) = Self::find_subquery(cond) else {
+            return Ok(cond.to_string());
+        };
 
-                    if and_chars == ['A', 'N', 'D'] && chars.peek().map_or(true, |c| c.is_whitespace()) {
-                        // 确认是AND关键字
-                        parts.push(current_part.trim().to_string());
-                        current_part.clear();
-                    } else {
-                        // 不是完整的AND，把字符加回去
-                        current_part.push(c);
-                        current_part.extend(&and_chars[1..]);
+        // 找到与 ( 匹配的 )，忽略引号内的括号
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut end = None;
+        for (i, c) in cond[start..].char_indices() {
+            match (c, quote) {
+                (q, Some(open)) if q == open => quote = None,
+                (_, Some(_)) => {}
+                ('\'' | '"', None) => quote = Some(c),
+                ('(', None) => depth += 1,
+                (')', None) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(start + i);
+                        break;
                     }
                 }
-                _ => current_part.push(c),
+                _ => {}
             }
         }
-        parts.push(current_part.trim().to_string());
-        
-        //println!("[DEBUG] Split parts: {:?}", parts);
+        let end = end.ok_or("Unterminated subquery in WHERE clause")?;
+
+        let rows = match crate::parser::parse_sql(&cond[start + 1..end])? {
+            crate::parser::SqlAst::Select { table, columns, where_clause, group_by, order_by, limit, offset, .. } => {
+                if columns.len() != 1 || columns[0] == "*" {
+                    return Err("Subquery must return exactly one column".into());
+                }
+                let cols: Vec<&str> = columns.iter().map(|c| c.as_str()).collect();
+                let order: Vec<(&str, bool)> = order_by.iter().map(|(c, d)| (c.as_str(), *d)).collect();
+                if group_by.is_empty() {
+                    self.select(&table, cols, where_clause.as_deref(), Some(order), limit, offset)?
+                } else {
+                    let keys: Vec<&str> = group_by.iter().map(|c| c.as_str()).collect();
+                    self.select_grouped(&table, cols, where_clause.as_deref(), keys, Some(order), limit, offset)?
+                }
+            }
+            _ => return Err("Only SELECT is supported in subqueries".into()),
+        };
+
+        let quoted: Vec<String> = rows.into_iter()
+            .map(|row| format!("'{}'", row[0].replace('\'', "''")))
+            .collect();
+        static TRAILING_IN_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"(?i)\bIN\s*$").unwrap());
+        let is_in_list = TRAILING_IN_RE.is_match(&cond[..start]);
+        let literal = if is_in_list {
+            format!("({})", quoted.join(", "))
+        } else {
+            match quoted.len() {
+                0 => "NULL".to_string(),
+                1 => quoted[0].clone(),
+                _ => return Err("Subquery returned more than one row".into()),
+            }
+        };
 
+        // 继续处理后面的子查询
+        let rest = self.resolve_subqueries(&cond[end + 1..])?;
+        Ok(format!("{}{}{}", &cond[..start], literal, rest))
+    }
+
+    // 去掉条件值两侧的引号，并还原 '' / "" 转义
+    fn unquote_condition_value(raw: &str) -> String {
+        for quote in ['\'', '"'] {
+            if raw.len() >= 2 && raw.starts_with(quote) && raw.ends_with(quote) {
+                let escaped = format!("{}{}", quote, quote);
+                return raw[1..raw.len() - 1].replace(&escaped, &quote.to_string());
+            }
+        }
+        raw.to_string()
+    }
+
+    // 取出条件左侧的操作数（括号和引号内的空白不作为分隔）
+    fn split_condition_operand(cond: &str) -> (&str, &str) {
+        let cond = cond.trim_start();
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        for (i, c) in cond.char_indices() {
+            match (c, quote) {
+                (q, Some(open)) if q == open => quote = None,
+                (_, Some(_)) => {}
+                ('"' | '\'' | '`', None) => quote = Some(c),
+                ('(', None) => depth += 1,
+                (')', None) => depth -= 1,
+                (c, None) if c.is_whitespace() && depth == 0 => return (&cond[..i], &cond[i..]),
+                _ => {}
+            }
+        }
+        (cond, "")
+    }
+
+    fn parse_and_condition(
+        cond: &str,
+        table: &Table,
+    ) -> Result<Box<dyn Fn(&[Value]) -> bool>, String> {
+        // 逐字符扫描一次，按顶层的 AND 切分（引号和括号内的不算），不递归
+        let parts = Self::split_and_parts(cond);
+
+        // AND 只出现在引号或括号内时就是单个条件
         if parts.len() < 2 {
-            return Err("Invalid AND condition".into());
+            return Self::parse_single_condition(cond, table);
         }
 
         // 解析各个子条件
-        let mut conditions = Vec::new();
+        let mut conditions = Vec::with_capacity(parts.len());
         for part in parts {
-            //println!("[DEBUG] Parsing part: '{}'", part);
-            let cond = Self::parse_single_condition(&part, table)?;
-            conditions.push(cond);
+            if part.is_empty() {
+                return Err("Invalid AND condition".into());
+            }
+            // 子条件本身可以是带括号的分组
+            conditions.push(Self::parse_condition(part, table)?);
         }
 
         // 组合条件
@@ -583,7 +1577,42 @@ impl Database {
         }))
     }
 
+    // 按顶层的 AND 关键字切分条件，AND 两侧必须是空白
+    fn split_and_parts(cond: &str) -> Vec<&str> {
+        let bytes = cond.as_bytes();
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut quote: Option<u8> = None;
+        let mut depth = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            match (bytes[i], quote) {
+                (q, Some(open)) if q == open => quote = None,
+                (_, Some(_)) => {}
+                (b'\'' | b'"' | b'`', None) => quote = Some(bytes[i]),
+                (b'(', None) => depth += 1,
+                (b')', None) => depth -= 1,
+                (_, None) if depth == 0
+                    && i > 0
+                    && bytes[i - 1].is_ascii_whitespace()
+                    && bytes[i..].len() > 3
+                    && bytes[i..i + 3].eq_ignore_ascii_case(b"AND")
+                    && bytes[i + 3].is_ascii_whitespace() => {
+                    parts.push(cond[start..i].trim());
+                    i += 3;
+                    start = i;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        parts.push(cond[start..].trim());
+        parts
+    }
+
     pub fn undo(&mut self) -> Result<usize, String> {
+        self.check_writable()?;
         self.restore_snapshot()
     }
 
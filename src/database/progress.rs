@@ -0,0 +1,28 @@
+use std::fmt;
+
+// 进度回调，参数为已处理的行数
+pub type ProgressCallback = Box<dyn FnMut(usize) + Send>;
+
+// 长时间的 UPDATE/DELETE/CSV 导入每处理 every 行调用一次回调
+pub(crate) struct ProgressReporter {
+    every: usize,
+    callback: ProgressCallback,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(every: usize, callback: ProgressCallback) -> Self {
+        Self { every: every.max(1), callback }
+    }
+
+    pub(crate) fn tick(&mut self, processed: usize) {
+        if processed.is_multiple_of(self.every) {
+            (self.callback)(processed);
+        }
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProgressReporter(every {} rows)", self.every)
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+
+use super::function::{FunctionRegistry, SharedFunction};
+use super::{DataType, Table, Value};
+
+// SELECT 投影表达式（列、字面量、函数调用、CAST 或整数加减）
+#[derive(Debug, Clone)]
+pub enum ProjectionExpr {
+    Column(usize),
+    Literal(String),
+    Function {
+        name: String,
+        args: Vec<ProjectionExpr>,
+    },
+    // 通过 Database::register_function 注册的函数
+    Custom {
+        func: CustomFunction,
+        args: Vec<ProjectionExpr>,
+    },
+    Cast {
+        expr: Box<ProjectionExpr>,
+        target: DataType,
+    },
+    // 任一侧为 NULL 时结果为 NULL
+    Arithmetic {
+        op: ArithmeticOp,
+        left: Box<ProjectionExpr>,
+        right: Box<ProjectionExpr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithmeticOp {
+    Add,
+    Sub,
+}
+
+#[derive(Clone)]
+pub struct CustomFunction {
+    name: String,
+    func: SharedFunction,
+}
+
+impl fmt::Debug for CustomFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// 支持的内置函数（自定义函数不能与之重名）
+pub(crate) const BUILTIN_FUNCTIONS: &[&str] = &["CONCAT"];
+
+impl ProjectionExpr {
+    // 编译投影表达式文本，例如 `name` 或 `CONCAT(first, ' ', last)`
+    pub fn compile(expr: &str, table: &Table) -> Result<Self, String> {
+        Self::compile_with(expr, table, &FunctionRegistry::default())
+    }
+
+    // 编译时同时查找自定义函数（内置函数优先）
+    pub(crate) fn compile_with(expr: &str, table: &Table, functions: &FunctionRegistry) -> Result<Self, String> {
+        let tokens = tokenize(expr)?;
+        let mut pos = 0;
+        let compiled = parse_expr(&tokens, &mut pos, table, functions)?;
+        if pos != tokens.len() {
+            return Err(format!("Unexpected trailing input in column expression: {}", expr));
+        }
+        Ok(compiled)
+    }
+
+    // 对一行数据求值
+    pub fn eval(&self, row: &[Value]) -> Result<Value, String> {
+        match self {
+            ProjectionExpr::Column(idx) => Ok(row[*idx].clone()),
+            ProjectionExpr::Literal(value) if value.is_empty() => Ok(Value::Null),
+            ProjectionExpr::Literal(value) => Ok(Value::Text(value.clone())),
+            ProjectionExpr::Function { name, args } => {
+                let values = args.iter()
+                    .map(|arg| arg.eval(row).map(|v| v.to_string()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match name.as_str() {
+                    "CONCAT" => Ok(Value::Text(values.concat())),
+                    _ => Err(format!("Unsupported function: {}", name)),
+                }
+            }
+            ProjectionExpr::Custom { func, args } => {
+                let values = args.iter()
+                    .map(|arg| arg.eval(row).map(|v| v.to_string()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let result = (func.func)(&values);
+                Ok(if result.is_empty() { Value::Null } else { Value::Text(result) })
+            }
+            ProjectionExpr::Cast { expr, target } => cast_value(expr.eval(row)?, target),
+            ProjectionExpr::Arithmetic { op, left, right } => {
+                let (left, right) = (left.eval(row)?, right.eval(row)?);
+                if left.is_null() || right.is_null() {
+                    return Ok(Value::Null);
+                }
+                let (a, b) = (integer_operand(&left)?, integer_operand(&right)?);
+                let result = match op {
+                    ArithmeticOp::Add => a.checked_add(b),
+                    ArithmeticOp::Sub => a.checked_sub(b),
+                };
+                result.map(Value::Int).ok_or_else(|| "Integer overflow in column expression".to_string())
+            }
+        }
+    }
+}
+
+// 在 Int 与 Varchar 之间转换，NULL 保持为 NULL
+fn cast_value(value: Value, target: &DataType) -> Result<Value, String> {
+    match (value, target) {
+        (Value::Text(s), DataType::Int(_)) => s.trim().parse::<i64>()
+            .map(Value::Int)
+            .map_err(|_| format!("Cannot cast '{}' to INT", s)),
+        (Value::Int(n), DataType::Varchar(_)) => Ok(Value::Text(n.to_string())),
+        (value, _) => Ok(value),
+    }
+}
+
+// 加减运算的操作数：INT 值或可以解析为整数的文本
+fn integer_operand(value: &Value) -> Result<i64, String> {
+    match value {
+        Value::Int(n) => Ok(*n),
+        other => other.to_string().trim().parse()
+            .map_err(|_| format!("Value '{}' is not INT in column expression", other)),
+    }
+}
+
+// 解析 CAST 的目标类型
+fn parse_cast_target(name: &str) -> Result<DataType, String> {
+    match name.to_uppercase().as_str() {
+        "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "SIGNED" => Ok(DataType::Int(0)),
+        "VARCHAR" | "CHAR" | "TEXT" | "STRING" => Ok(DataType::Varchar(0)),
+        other => Err(format!("Unsupported CAST target type: {}", other)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ProjToken {
+    Ident(String),
+    QuotedIdent(String),
+    Str(String),
+    Number(String),
+    Comma,
+    LeftParen,
+    RightParen,
+    Plus,
+    Minus,
+}
+
+// 分词：标识符、字符串、数字、逗号、括号和加减号
+fn tokenize(expr: &str) -> Result<Vec<ProjToken>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                chars.next();
+            }
+            ',' => {
+                chars.next();
+                tokens.push(ProjToken::Comma);
+            }
+            '(' => {
+                chars.next();
+                tokens.push(ProjToken::LeftParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(ProjToken::RightParen);
+            }
+            '\'' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        // '' 表示字面量中的单引号
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            s.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(ch) => s.push(ch),
+                        None => return Err("Unterminated string literal".into()),
+                    }
+                }
+                tokens.push(ProjToken::Str(s));
+            }
+            '"' | '`' => {
+                // 带引号的标识符（允许保留字作为列名）
+                chars.next();
+                let mut ident = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => ident.push(ch),
+                        None => return Err("Unterminated quoted identifier".into()),
+                    }
+                }
+                tokens.push(ProjToken::QuotedIdent(ident));
+            }
+            '+' => {
+                chars.next();
+                tokens.push(ProjToken::Plus);
+            }
+            // 紧跟在操作数后面的 - 是减号，其余情况是负数的符号
+            '-' if matches!(tokens.last(), Some(
+                ProjToken::Ident(_) | ProjToken::QuotedIdent(_) | ProjToken::Str(_) | ProjToken::Number(_) | ProjToken::RightParen
+            )) => {
+                chars.next();
+                tokens.push(ProjToken::Minus);
+            }
+            '0'..='9' | '-' => {
+                let mut num = String::new();
+                num.push(c);
+                chars.next();
+                while let Some(&d) = chars.peek() {
+                    if d.is_ascii_digit() || d == '.' {
+                        num.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(ProjToken::Number(num));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_alphanumeric() || d == '_' || d == '.' {
+                        ident.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(ProjToken::Ident(ident));
+            }
+            _ => return Err(format!("Unexpected character '{}' in column expression", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+// 操作数之间以 + / - 连接，从左到右结合
+fn parse_expr(
+    tokens: &[ProjToken],
+    pos: &mut usize,
+    table: &Table,
+    functions: &FunctionRegistry,
+) -> Result<ProjectionExpr, String> {
+    let mut expr = parse_operand(tokens, pos, table, functions)?;
+    loop {
+        let op = match tokens.get(*pos) {
+            Some(ProjToken::Plus) => ArithmeticOp::Add,
+            Some(ProjToken::Minus) => ArithmeticOp::Sub,
+            _ => return Ok(expr),
+        };
+        *pos += 1;
+        let right = parse_operand(tokens, pos, table, functions)?;
+        expr = ProjectionExpr::Arithmetic { op, left: Box::new(expr), right: Box::new(right) };
+    }
+}
+
+fn parse_operand(
+    tokens: &[ProjToken],
+    pos: &mut usize,
+    table: &Table,
+    functions: &FunctionRegistry,
+) -> Result<ProjectionExpr, String> {
+    let token = tokens.get(*pos).ok_or("Unexpected end of column expression")?;
+    *pos += 1;
+
+    match token {
+        ProjToken::Str(s) | ProjToken::Number(s) => Ok(ProjectionExpr::Literal(s.clone())),
+        // 括号分组，例如 (a + 1) - b
+        ProjToken::LeftParen => {
+            let expr = parse_expr(tokens, pos, table, functions)?;
+            if tokens.get(*pos) != Some(&ProjToken::RightParen) {
+                return Err("Expected ')' in column expression".into());
+            }
+            *pos += 1;
+            Ok(expr)
+        }
+        ProjToken::Ident(name)
+            if name.eq_ignore_ascii_case("CAST") && tokens.get(*pos) == Some(&ProjToken::LeftParen) =>
+        {
+            // CAST(expr AS type[(n)])
+            *pos += 1;
+            let expr = parse_expr(tokens, pos, table, functions)?;
+            match tokens.get(*pos) {
+                Some(ProjToken::Ident(kw)) if kw.eq_ignore_ascii_case("AS") => *pos += 1,
+                _ => return Err("Expected AS in CAST".into()),
+            }
+            let target = match tokens.get(*pos) {
+                Some(ProjToken::Ident(ty)) => parse_cast_target(ty)?,
+                _ => return Err("Expected type name in CAST".into()),
+            };
+            *pos += 1;
+            // 忽略类型长度，例如 VARCHAR(10)
+            if tokens.get(*pos) == Some(&ProjToken::LeftParen) {
+                match (tokens.get(*pos + 1), tokens.get(*pos + 2)) {
+                    (Some(ProjToken::Number(_)), Some(ProjToken::RightParen)) => *pos += 3,
+                    _ => return Err("Invalid type length in CAST".into()),
+                }
+            }
+            if tokens.get(*pos) != Some(&ProjToken::RightParen) {
+                return Err("Expected ')' after CAST".into());
+            }
+            *pos += 1;
+            Ok(ProjectionExpr::Cast { expr: Box::new(expr), target })
+        }
+        ProjToken::Ident(name) if tokens.get(*pos) == Some(&ProjToken::LeftParen) => {
+            *pos += 1;
+            let name = name.to_uppercase();
+            let custom = if BUILTIN_FUNCTIONS.contains(&name.as_str()) {
+                None
+            } else {
+                Some(functions.get(&name).ok_or(format!("Unsupported function: {}", name))?)
+            };
+
+            let mut args = Vec::new();
+            if tokens.get(*pos) == Some(&ProjToken::RightParen) {
+                *pos += 1;
+            } else {
+                loop {
+                    args.push(parse_expr(tokens, pos, table, functions)?);
+                    match tokens.get(*pos) {
+                        Some(ProjToken::Comma) => *pos += 1,
+                        Some(ProjToken::RightParen) => {
+                            *pos += 1;
+                            break;
+                        }
+                        _ => return Err(format!("Expected ',' or ')' in call to {}", name)),
+                    }
+                }
+            }
+            Ok(match custom {
+                Some(func) => ProjectionExpr::Custom { func: CustomFunction { name, func }, args },
+                None => ProjectionExpr::Function { name, args },
+            })
+        }
+        ProjToken::Ident(name) if name.eq_ignore_ascii_case("NULL") => {
+            Ok(ProjectionExpr::Literal(String::new()))
+        }
+        ProjToken::Ident(name) | ProjToken::QuotedIdent(name) => {
+            let idx = table.column_index(name)
+                .ok_or(format!("Column '{}' not found", name))?;
+            Ok(ProjectionExpr::Column(idx))
+        }
+        _ => Err("Invalid column expression".into()),
+    }
+}
@@ -0,0 +1,33 @@
+use super::random::SplitMix64;
+use super::Database;
+
+impl Database {
+    // 返回表的前 n 行（按插入顺序），只遍历需要的行
+    pub fn sample(&self, table_name: &str, n: usize) -> Result<Vec<Vec<String>>, String> {
+        let rows = self.scan(table_name)
+            .ok_or(format!("Table '{}' not found", table_name))?;
+        Ok(rows.take(n).map(|row| row.iter().map(|v| v.to_string()).collect()).collect())
+    }
+
+    // 按种子随机抽取 n 行（蓄水池抽样，结果保持原有行顺序）；行数不足 n 时返回全部行
+    pub fn sample_random(&self, table_name: &str, n: usize, seed: u64) -> Result<Vec<Vec<String>>, String> {
+        let rows = self.scan(table_name)
+            .ok_or(format!("Table '{}' not found", table_name))?;
+        let mut rng = SplitMix64::new(seed);
+        let mut reservoir = Vec::with_capacity(n);
+        for (i, row) in rows.enumerate() {
+            if reservoir.len() < n {
+                reservoir.push((i, row));
+            } else {
+                let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+                if j < n {
+                    reservoir[j] = (i, row);
+                }
+            }
+        }
+        reservoir.sort_by_key(|(i, _)| *i);
+        Ok(reservoir.into_iter()
+            .map(|(_, row)| row.iter().map(|v| v.to_string()).collect())
+            .collect())
+    }
+}
@@ -0,0 +1,85 @@
+use serde::Serialize;
+
+use super::{Column, DataType, Database, Table};
+
+// 只包含结构、不包含数据的视图，供外部工具读取
+#[derive(Serialize)]
+struct SchemaView<'a> {
+    tables: Vec<TableSchema<'a>>,
+}
+
+#[derive(Serialize)]
+struct TableSchema<'a> {
+    name: &'a str,
+    columns: Vec<ColumnSchema<'a>>,
+}
+
+#[derive(Serialize)]
+struct ColumnSchema<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    type_name: &'static str,
+    width: u32,
+    primary_key: bool,
+    not_null: bool,
+    default: Option<&'a str>,
+    comment: Option<&'a str>,
+}
+
+impl<'a> From<&'a Table> for TableSchema<'a> {
+    fn from(table: &'a Table) -> Self {
+        Self {
+            name: &table.name,
+            columns: table.columns.iter().map(ColumnSchema::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a Column> for ColumnSchema<'a> {
+    fn from(col: &'a Column) -> Self {
+        let (type_name, width) = match col.data_type {
+            DataType::Int(width) => ("INT", width),
+            DataType::Varchar(width) => ("VARCHAR", width),
+        };
+        Self {
+            name: &col.name,
+            type_name,
+            width,
+            primary_key: col.is_primary,
+            not_null: col.not_null,
+            default: col.default.as_deref(),
+            comment: col.comment.as_deref(),
+        }
+    }
+}
+
+impl Database {
+    // DESCRIBE 的输出：每列一行（列名、类型、主键、是否可为空、默认值、注释）
+    pub fn describe(&self, table_name: &str) -> Result<Vec<Vec<String>>, String> {
+        let table = self.table(table_name)
+            .ok_or(format!("Table '{}' not found", table_name))?;
+        Ok(table.columns.iter().map(|col| {
+            let type_name = match col.data_type {
+                DataType::Int(width) => format!("INT({})", width),
+                DataType::Varchar(width) => format!("VARCHAR({})", width),
+            };
+            vec![
+                col.name.clone(),
+                type_name,
+                if col.is_primary { "PRI" } else { "" }.to_string(),
+                if col.not_null { "NO" } else { "YES" }.to_string(),
+                col.default.clone().unwrap_or_default(),
+                col.comment.clone().unwrap_or_default(),
+            ]
+        }).collect())
+    }
+
+    // 以 JSON 导出所有表的结构（列名、类型、宽度和约束），不包含行数据
+    pub fn schema_json(&self) -> String {
+        let view = SchemaView {
+            tables: self.tables_iter().map(TableSchema::from).collect(),
+        };
+        // 视图中只有字符串、数字和布尔值，序列化不会失败
+        serde_json::to_string_pretty(&view).unwrap()
+    }
+}
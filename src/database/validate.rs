@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::{DataType, Database, Table, Value};
+
+// validate() 发现的数据不一致；row 为行号（从 1 开始）
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IntegrityError {
+    #[error("Table '{table}' row {row}: expected {expected} cells, found {found}")]
+    ArityMismatch { table: String, row: usize, expected: usize, found: usize },
+    #[error("Table '{table}': duplicate primary key '{key}' (rows {first_row} and {row})")]
+    DuplicateKey { table: String, key: String, first_row: usize, row: usize },
+    #[error("Table '{table}' row {row}: column '{column}' cannot be null")]
+    NullInNotNull { table: String, row: usize, column: String },
+    #[error("Table '{table}' row {row}: value '{value}' does not match type of column '{column}'")]
+    TypeMismatch { table: String, row: usize, column: String, value: String },
+}
+
+impl Database {
+    // 检查所有表的每一行：单元格数量、主键重复、非空约束和列类型，报告全部问题
+    pub fn validate(&self) -> Result<(), Vec<IntegrityError>> {
+        let errors: Vec<IntegrityError> = self.tables_iter().flat_map(validate_table).collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // 与列类型不符的单元格（加载时无法转换、按文本保留的值）
+    pub fn type_mismatches(&self) -> Vec<IntegrityError> {
+        self.tables_iter()
+            .flat_map(validate_table)
+            .filter(|e| matches!(e, IntegrityError::TypeMismatch { .. }))
+            .collect()
+    }
+}
+
+fn validate_table(table: &Table) -> Vec<IntegrityError> {
+    let mut errors = Vec::new();
+    let pk_indices: Vec<usize> = table.columns.iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_primary)
+        .map(|(i, _)| i)
+        .collect();
+    let mut seen_keys: HashMap<Vec<&Value>, usize> = HashMap::new();
+
+    for (i, row) in table.data.iter().enumerate() {
+        let row_no = i + 1;
+        if row.len() != table.columns.len() {
+            errors.push(IntegrityError::ArityMismatch {
+                table: table.name.clone(),
+                row: row_no,
+                expected: table.columns.len(),
+                found: row.len(),
+            });
+            // 列无法对应，跳过这一行的其余检查
+            continue;
+        }
+
+        for (value, col) in row.iter().zip(&table.columns) {
+            let mismatch = match (value, &col.data_type) {
+                (Value::Null, _) => {
+                    if col.not_null || col.is_primary {
+                        errors.push(IntegrityError::NullInNotNull {
+                            table: table.name.clone(),
+                            row: row_no,
+                            column: col.name.clone(),
+                        });
+                    }
+                    false
+                }
+                (Value::Int(_), DataType::Int(_)) => false,
+                (Value::Text(s), DataType::Varchar(max_len)) => s.chars().count() > *max_len as usize,
+                _ => true,
+            };
+            if mismatch {
+                errors.push(IntegrityError::TypeMismatch {
+                    table: table.name.clone(),
+                    row: row_no,
+                    column: col.name.clone(),
+                    value: value.to_string(),
+                });
+            }
+        }
+
+        if pk_indices.is_empty() || pk_indices.iter().any(|&idx| row[idx].is_null()) {
+            continue;
+        }
+        let key: Vec<&Value> = pk_indices.iter().map(|&idx| &row[idx]).collect();
+        if let Some(&first_row) = seen_keys.get(&key) {
+            errors.push(IntegrityError::DuplicateKey {
+                table: table.name.clone(),
+                key: key.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "),
+                first_row,
+                row: row_no,
+            });
+        } else {
+            seen_keys.insert(key, row_no);
+        }
+    }
+    errors
+}
@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::DataType;
+
+// 单元格的值，插入时按列类型解析一次
+// 排序时 NULL 最小，其次是整数，最后是文本
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Text(String),
+}
+
+impl Value {
+    // 按列类型解析原始文本，空字符串表示 NULL
+    pub fn parse(raw: &str, data_type: &DataType) -> Result<Self, String> {
+        if raw.is_empty() {
+            return Ok(Value::Null);
+        }
+        match data_type {
+            DataType::Int(_) => raw.trim().parse::<i64>()
+                .map(Value::Int)
+                .map_err(|_| format!("Value '{}' is not INT", raw)),
+            DataType::Varchar(_) => Ok(Value::Text(raw.to_string())),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    // 作为布尔值解释：整数非 0 为真，文本接受 true/false；其余视为未知
+    pub fn truth(&self) -> Option<bool> {
+        match self {
+            Value::Null => None,
+            Value::Int(n) => Some(*n != 0),
+            Value::Text(s) if s.eq_ignore_ascii_case("true") => Some(true),
+            Value::Text(s) if s.eq_ignore_ascii_case("false") => Some(false),
+            Value::Text(_) => None,
+        }
+    }
+
+    // 与条件中的字面量比较；NULL 或类型不匹配时返回 None（不满足任何比较）
+    pub fn compare_literal(&self, literal: &str) -> Option<Ordering> {
+        match self {
+            Value::Null => None,
+            Value::Int(n) => literal.trim().parse::<i64>().ok().map(|v| n.cmp(&v)),
+            Value::Text(s) => Some(s.as_str().cmp(literal)),
+        }
+    }
+
+    // 转换为另一种列类型（用于从文件加载旧数据）
+    pub(crate) fn coerce(self, data_type: &DataType) -> Result<Self, String> {
+        match (self, data_type) {
+            (Value::Text(s), DataType::Int(_)) => Value::parse(&s, data_type),
+            (Value::Int(n), DataType::Varchar(_)) => Ok(Value::Text(n.to_string())),
+            (value, _) => Ok(value),
+        }
+    }
+}
+
+// ORDER BY 中两个值的先后
+pub(crate) fn compare_for_sort(a: &Value, b: &Value, desc: bool, nulls_first: Option<bool>) -> Ordering {
+    compare_nullable(a.is_null(), b.is_null(), desc, nulls_first, || a.cmp(b))
+}
+
+// 先按是否为 NULL 排，两边都不是 NULL 时才调用 compare；nulls_first 为 None 时 NULL 视为最小值
+// （升序排在最前、降序排在最后）。与列类型无关，所有 NULL 总是排在一起
+pub(crate) fn compare_nullable(
+    a_null: bool,
+    b_null: bool,
+    desc: bool,
+    nulls_first: Option<bool>,
+    compare: impl FnOnce() -> Ordering,
+) -> Ordering {
+    let nulls_first = nulls_first.unwrap_or(!desc);
+    match (a_null, b_null) {
+        (true, true) => Ordering::Equal,
+        (true, false) if nulls_first => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (false, true) if nulls_first => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) if desc => compare().reverse(),
+        (false, false) => compare(),
+    }
+}
+
+// 显示形式：NULL 为空字符串
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Value::Null => other.is_empty(),
+            Value::Int(n) => other.parse::<i64>() == Ok(*n),
+            Value::Text(s) => s == other,
+        }
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+// 序列化为字符串（NULL 为 ""），与之前的文件格式保持兼容
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+// 反序列化时先读成文本，加载后再按列类型转换
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Ok(if raw.is_empty() { Value::Null } else { Value::Text(raw) })
+    }
+}
@@ -4,4 +4,37 @@ use thiserror::Error;
 pub enum DbError {
     #[error("Table already exists")]
     TableExists,
+    #[error("Table '{0}' not found")]
+    TableNotFound(String),
+    #[error("Database is locked by another process (remove '{0}' if no other instance is running)")]
+    Locked(String),
+    #[error("Database is read-only")]
+    ReadOnly,
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("Table '{0}' has a different schema")]
+    SchemaMismatch(String),
+    #[error("Duplicate entry '{1}' for key 'PRIMARY' in table '{0}'")]
+    DuplicateKey(String, String),
+    #[error("Failed to parse {0}")]
+    Parse(String),
+    #[error("{0}")]
+    Execution(String),
+    // 一行数据同时违反的多条约束
+    #[error("{}", .0.join("; "))]
+    Violations(Vec<String>),
+}
+
+// 执行语句时的字符串错误
+impl From<String> for DbError {
+    fn from(e: String) -> Self {
+        DbError::Execution(e)
+    }
+}
+
+// 兼容仍以 String 作为错误类型的接口
+impl From<DbError> for String {
+    fn from(e: DbError) -> Self {
+        e.to_string()
+    }
 }
@@ -1,9 +1,14 @@
-use crate::database::{Database, Table};
+use crate::database::{DataType, Database, Table};
 
 pub fn format_table(
     headers: Vec<String>,
     data: Vec<Vec<String>>,
 ) -> String {
+    // 没有列时无法画出表格（例如手工编辑出的空表结构）
+    if headers.is_empty() {
+        return format!("(no columns, {} row(s))", data.len());
+    }
+
     // 计算每列最大内容宽度（纯内容，不考虑空格）
     let mut content_widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
 
@@ -66,22 +71,19 @@ pub fn format_table_from_db(
     columns: Vec<&str>,
     data: Vec<Vec<String>>,
 ) -> Result<String, String> {
-    let table = db.tables
-        .iter()
-        .find(|t| t.name == table_name)
+    let table = db.table(table_name)
         .ok_or(format!("Table '{}' not found", table_name))?;
 
-    // 获取列名作为表头
-    let headers = if columns == ["*"] {
-        table.columns.iter().map(|c| c.name.clone()).collect()
-    } else {
-        columns.iter().map(|&col_name| {
-            table.columns.iter()
-                .find(|c| c.name == col_name)
-                .map(|c| c.name.clone())
-                .ok_or(format!("Column '{}' not found", col_name))
-        }).collect::<Result<Vec<_>, _>>()?
-    };
+    // 获取列名作为表头（* 展开为所有列，表达式或别名原样显示）
+    let headers: Vec<String> = columns.iter().flat_map(|&col_name| {
+        if col_name == "*" {
+            return table.columns.iter().map(|c| c.name.clone()).collect();
+        }
+        vec![table.columns.iter()
+            .find(|c| c.name == col_name)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| col_name.to_string())]
+    }).collect();
 
     // 验证列数匹配
     if !data.is_empty() && headers.len() != data[0].len() {
@@ -90,3 +92,67 @@ pub fn format_table_from_db(
 
     Ok(format_table(headers, data))
 }
+
+// 查询结果的每一列是否来自 INT 列（* 展开为所有列，表达式不算）
+pub fn int_columns(table: &Table, columns: &[impl AsRef<str>]) -> Vec<bool> {
+    columns.iter().map(AsRef::as_ref).flat_map(|col| {
+        if col == "*" {
+            return table.columns.iter().map(|c| matches!(c.data_type, DataType::Int(_))).collect();
+        }
+        vec![table.column_index(col).is_some_and(|i| matches!(table.columns[i].data_type, DataType::Int(_)))]
+    }).collect()
+}
+
+// 显示前对 INT 列的单元格按千位分组
+pub fn group_int_columns(rows: &mut [Vec<String>], is_int: &[bool]) {
+    for row in rows {
+        for (cell, _) in row.iter_mut().zip(is_int).filter(|(_, int)| **int) {
+            *cell = group_thousands(cell);
+        }
+    }
+}
+
+// 整数每三位插入一个逗号，例如 -1234567 -> -1,234,567；不是整数时原样返回
+pub fn group_thousands(value: &str) -> String {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return value.to_string();
+    }
+    let mut grouped = String::with_capacity(value.len() + digits.len() / 3);
+    grouped.push_str(sign);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+// 计算结果的显示方式
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NumberFormat {
+    pub scientific: bool,        // 数量级过大或过小时使用科学计数法
+    pub decimals: Option<usize>, // 小数位数（科学计数法时为尾数的小数位数），None 表示按原样输出
+}
+
+// 科学计数法的适用范围：|x| >= 1e12 或 0 < |x| < 1e-6
+const SCIENTIFIC_LARGE: f64 = 1e12;
+const SCIENTIFIC_SMALL: f64 = 1e-6;
+
+// 按设置格式化计算结果
+pub fn format_number(value: f64, fmt: &NumberFormat) -> String {
+    let magnitude = value.abs();
+    let use_scientific = fmt.scientific
+        && value.is_finite()
+        && (magnitude >= SCIENTIFIC_LARGE || (magnitude > 0.0 && magnitude < SCIENTIFIC_SMALL));
+    match (use_scientific, fmt.decimals) {
+        (true, Some(d)) => format!("{:.*e}", d, value),
+        (true, None) => format!("{:e}", value),
+        (false, Some(d)) => format!("{:.*}", d, value),
+        (false, None) => value.to_string(),
+    }
+}
@@ -1,4 +1,6 @@
 use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
 
 #[derive(Debug)]
 pub struct CommandHistory {
@@ -20,9 +22,23 @@ impl CommandHistory {
         }
     }
 
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    // 调整历史记录上限（至少为 1），缩小时从最旧的一端丢弃
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size.max(1);
+        while self.commands.len() > self.max_size {
+            self.commands.pop_front();
+        }
+        self.reset_index();
+    }
+
     pub fn is_history_command(cmd: &str) -> bool {
         let trimmed = cmd.trim();
-        trimmed == "!!" || trimmed.starts_with('!') && trimmed[1..].trim().parse::<usize>().is_ok()
+        // !!、!n 以及 !前缀 都是历史命令
+        trimmed.starts_with('!') && !trimmed[1..].trim().is_empty()
     }
 
     fn should_skip_command(cmd: &str) -> bool {
@@ -95,6 +111,30 @@ impl CommandHistory {
         })
     }
 
+    // 查找最近一条以 prefix 开头的命令（不区分大小写），返回其索引和完整命令
+    pub fn find_last_starting_with(&self, prefix: &str) -> Option<(usize, String)> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return None;
+        }
+        let index = self.commands.iter()
+            .rposition(|cmd| cmd.trim_start().to_lowercase().starts_with(&prefix))?;
+        self.get_full_command(index).map(|cmd| (index, cmd))
+    }
+
+    // 不区分大小写的子串搜索，返回所有匹配的 (索引, 命令)
+    pub fn search(&self, term: &str) -> Vec<(usize, &str)> {
+        let term = term.trim().to_lowercase();
+        if term.is_empty() {
+            return Vec::new();
+        }
+        self.commands.iter()
+            .enumerate()
+            .filter(|(_, cmd)| cmd.to_lowercase().contains(&term))
+            .map(|(i, cmd)| (i, cmd.as_str()))
+            .collect()
+    }
+
     pub fn clear(&mut self) {
         self.commands.clear();
         self.current_index = 0;
@@ -107,4 +147,25 @@ impl CommandHistory {
     pub fn enumerate(&self) -> impl Iterator<Item = (usize, &String)> {
         self.commands.iter().enumerate()
     }
+
+    // 将历史记录保存为 JSON 数组（多行命令可完整保留）
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&self.commands).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    // 从文件追加历史记录
+    pub fn load_from(&mut self, path: &Path) -> Result<(), String> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let commands: Vec<String> = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        for cmd in commands {
+            self.add(cmd);
+        }
+        Ok(())
+    }
 }
@@ -1,22 +1,40 @@
+pub mod cache;
 pub mod database;
 pub mod error;
 pub mod format;
 pub mod parser;
 pub mod history;
+pub mod repl;
 
 use crate::database::{Database, Table};
-use crate::format::{format_table, format_table_from_db};
-use crate::parser::{parse_sql, SqlAst};
+use crate::error::DbError;
+use crate::format::{format_number, format_table, group_int_columns, int_columns};
+use crate::parser::{SqlAst, NUMERIC_OVERFLOW};
+use crate::repl::ReplSettings;
 pub use history::CommandHistory;
 
-// 添加注释处理函数
+// 添加注释处理函数（引号内的 -- 和 /* 不视为注释）
 fn remove_comments(input: &str) -> String {
     let mut in_block_comment = false;
     let mut in_line_comment = false;
+    let mut quote: Option<char> = None;
     let mut result = String::new();
     let mut chars = input.chars().peekable();
 
     while let Some(c) = chars.next() {
+        // 字符串/标识符引号内的内容原样保留
+        if let Some(q) = quote {
+            result.push(c);
+            if c == '\\' {
+                if let Some(next) = chars.next() {
+                    result.push(next);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+
         match (c, in_block_comment, in_line_comment) {
             // 检测块注释开始
             ('/', false, false) if chars.peek() == Some(&'*') => {
@@ -28,6 +46,10 @@ fn remove_comments(input: &str) -> String {
                 in_block_comment = false;
                 chars.next(); // 跳过'/'
             },
+            // 块注释内保留换行符，保证行号正确
+            ('\n', true, _) => {
+                result.push(c);
+            },
             // 检测行注释开始
             ('-', false, false) if chars.peek() == Some(&'-') => {
                 in_line_comment = true;
@@ -38,6 +60,11 @@ fn remove_comments(input: &str) -> String {
                 in_line_comment = false;
                 result.push(c); // 保留换行符
             },
+            // 进入引号
+            ('\'' | '"' | '`', false, false) => {
+                quote = Some(c);
+                result.push(c);
+            },
             // 有效字符处理
             (c, false, false) => {
                 result.push(c);
@@ -49,167 +76,400 @@ fn remove_comments(input: &str) -> String {
     result
 }
 
-pub fn execute_sql(
-    sql_statement: &str,
-    db: &mut database::Database,
-    history: &mut history::CommandHistory
-) -> bool {
-    if sql_statement.trim().to_uppercase() == "HISTORY" {
-        return false;
-    }
-    // 处理注释
-    let clean_sql = remove_comments(sql_statement);
+// 按分号拆分多条语句，忽略引号内的分号（如 VALUES ('a;b')）
+pub fn split_statements(input: &str) -> Vec<String> {
+    split_statements_with_lines(input)
+        .into_iter()
+        .map(|(_, stmt)| stmt)
+        .collect()
+}
 
-    // 分割SQL语句（支持分号分隔的多条语句）
-    let statements: Vec<&str> = clean_sql.split(';')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+// 拆分语句并返回每条语句起始的行号（从 1 开始）
+pub fn split_statements_with_lines(input: &str) -> Vec<(usize, String)> {
+    let clean_sql = remove_comments(input);
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = clean_sql.chars();
+    let mut line = 1;
+    let mut start_line = None;
 
-    let mut has_output = false;
-    let mut has_error = false;
-    let statements_len = statements.len();
+    while let Some(c) = chars.next() {
+        if start_line.is_none() && !c.is_whitespace() && c != ';' {
+            start_line = Some(line);
+        }
+        if c == '\n' {
+            line += 1;
+        }
 
-    // 处理每条SQL语句
-    for stmt in statements {
-        match parse_sql(stmt) {
-            Ok(ast) => {
-                match ast {
-                    SqlAst::Select { table, columns, where_clause, order_by } => {
-                        let cols_ref: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
-                        let cond_str = where_clause.as_deref();
-                        let order_by_ref = order_by.iter()
-                            .map(|(col, desc)| (col.as_str(), *desc))
-                            .collect::<Vec<_>>();
-
-                        match db.select(&table, cols_ref, cond_str, Some(order_by_ref)) {
-                            Ok(data) if !data.is_empty() => {
-                                has_output = true;
-                                let formatted = format_table_from_db(
-                                    &db, 
-                                    &table, 
-                                    columns.iter().map(|s| s.as_str()).collect(), 
-                                    data
-                                );
-                                match formatted {
-                                    Ok(table_str) => println!("{}\n", table_str),
-                                    Err(e) => {
-                                        eprintln!("{}", e);
-                                        has_error = true;
-                                    },
-                                }
-                            }
-                            Ok(_) => {} // 空结果不输出
-                            Err(e) => {
-                                eprintln!("{}", e);
-                                has_error = true;
-                            },
-                        }
-                    }
-                    SqlAst::Calculate { expression, result } => {
-                        has_output = true;
-                        let headers = vec![expression];
-                        let data = vec![vec![result.to_string()]];
-                        println!("{}\n", format_table(headers, data));
-                    }
-                    SqlAst::CreateTable { table_name, columns } => {
-                        let col_defs: Vec<(&str, _, bool, bool)> = columns.iter()
-                            .map(|(name, dt, pk, nn)| (name.as_str(), dt.clone(), *pk, *nn))
-                            .collect();
-                        if let Err(e) = db.create_table(&table_name, col_defs) {
-                            eprintln!("{}", e);
-                            has_error = true;
-                        }
-                    }
-                    SqlAst::Insert { table, columns, values } => {
-                        let values_ref: Vec<Vec<&str>> = values.iter()
-                            .map(|row| row.iter().map(|s| s.as_str()).collect())
-                            .collect();
-                        match db.insert(&table, columns, values_ref) {
-                            Ok(count) => {
-                                has_output = true;
-                                println!("{} row(s) inserted\n", count);
-                            }
-                            Err(e) => {
-                                // 特殊处理主键重复错误
-                                if e.contains("Duplicate entry") {
-                                    let value = e.split("'").nth(1).unwrap_or("");
-                                    eprintln!("Error: Duplicate entry '{}' for key 'PRIMARY'", value);
-                                } else if e.contains("cannot be null") {
-                                    let col_name = e.split("'").nth(1).unwrap_or("");
-                                    eprintln!("Field '{}' doesn't have a default value", col_name);
-                                } else {
-                                    eprintln!("{}", e);
-                                }
-                                has_error = true;
-                            },
-                        }
-                    }
-                    SqlAst::Update { table, set, where_clause } => {
-                        let cond_str = where_clause.as_deref();
-                        match db.update(&table, set, cond_str) {
-                            Ok(count) => {
-                                has_output = true;
-                                println!("{} row(s) updated\n", count);
-                            }
-                            Err(e) => {
-                                if e.contains("Duplicate entry") {
-                                    let value = e.split("'").nth(1).unwrap_or("");
-                                    eprintln!("Error: Duplicate entry '{}' for key 'PRIMARY'", value);
-                                } else if e.contains("cannot be null") {
-                                    let col_name = e.split("'").nth(1).unwrap_or("");
-                                    eprintln!("Field '{}' doesn't have a default value", col_name);
-                                } else {
-                                    eprintln!("{}", e);
-                                }
-                                has_error = true;
-                            },
-                        }
-                    }
-                    SqlAst::Delete { table, where_clause } => {
-                        let cond_str = where_clause.as_deref();
-                        match db.delete(&table, cond_str) {
-                            Ok(count) => {
-                                has_output = true;
-                                println!("{} row(s) deleted\n", count);
-                            }
-                            Err(e) => {
-                                eprintln!("{}", e);
-                                has_error = true;
-                            },
-                        }
-                    }
-                    SqlAst::Drop { tables, if_exists } => {
-                        match db.drop_tables(&tables, if_exists) {
-                            Ok(count) => {
-                                has_output = true;
-                                println!("Dropped {} table(s)\n", count);
-                            }
-                            Err(e) => {
-                                eprintln!("{}", e);
-                                has_error = true;
-                            },
+        match quote {
+            Some(q) => {
+                current.push(c);
+                if c == '\\' {
+                    if let Some(next) = chars.next() {
+                        if next == '\n' {
+                            line += 1;
                         }
+                        current.push(next);
                     }
+                } else if c == q {
+                    quote = None;
                 }
             }
-            Err(e) => {
-                eprintln!("Error: Syntax error");
-                has_error = true;
-            }
+            None => match c {
+                '\'' | '"' | '`' => {
+                    quote = Some(c);
+                    current.push(c);
+                }
+                ';' => {
+                    if !current.trim().is_empty() {
+                        statements.push((start_line.unwrap_or(line), current.trim().to_string()));
+                    }
+                    current.clear();
+                    start_line = None;
+                }
+                _ => current.push(c),
+            },
         }
     }
 
-    // 如果没有输出任何结果（且没有错误），显示提示信息
-    if !has_output && !has_error && statements_len > 0 {
-        println!("There are no results to be displayed.");
+    if !current.trim().is_empty() {
+        statements.push((start_line.unwrap_or(line), current.trim().to_string()));
     }
 
-    // 保存数据库
+    statements
+}
+
+// 执行 SQL 并保存数据库
+pub fn execute_sql(sql_statement: &str, db: &mut database::Database, settings: &ReplSettings) -> bool {
+    let ok = execute_statements(sql_statement, db, settings);
+
+    // 保存数据库（只读模式下跳过）
+    if db.is_read_only() {
+        return ok;
+    }
     if let Err(e) = db.save() {
         eprintln!("Failed to save database: {}", e);
         return false;
     }
 
+    ok
+}
+
+// 执行 SQL 但不保存（用于关闭自动保存的场景）
+pub fn execute_statements(sql_statement: &str, db: &mut database::Database, settings: &ReplSettings) -> bool {
+    if sql_statement.trim().to_uppercase() == "HISTORY" {
+        return false;
+    }
+    // 去除注释并分割SQL语句（支持分号分隔的多条语句）
+    let statements = split_statements(sql_statement);
+
+    let mut has_output = false;
+    let mut has_error = false;
+    let statements_len = statements.len();
+
+    // 处理每条SQL语句
+    for stmt in &statements {
+        execute_statement(stmt, db, settings, &mut has_output, &mut has_error);
+    }
+
+    // 如果没有输出任何结果（且没有错误），显示提示信息
+    if !has_output && !has_error && statements_len > 0 {
+        println!("There are no results to be displayed.");
+    }
+
+    !has_error
+}
+
+// 将整个输入作为一条语句执行，不按分号拆分（供已持有单条语句的调用方使用，不保存）
+pub fn execute_single(sql: &str, db: &mut Database, settings: &ReplSettings) -> bool {
+    let mut has_output = false;
+    let mut has_error = false;
+    execute_statement(sql.trim(), db, settings, &mut has_output, &mut has_error);
     !has_error
 }
+
+// 单条语句的执行结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryResult {
+    // 查询、计算、ANALYZE 等返回的表格
+    Rows {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Created,
+    Inserted(usize),
+    Updated(usize),
+    Deleted(usize),
+    Dropped(usize),
+}
+
+// 解析并执行一条语句，打印结果或错误
+fn execute_statement(stmt: &str, db: &mut Database, settings: &ReplSettings, has_output: &mut bool, has_error: &mut bool) {
+    match run_statement_typed(stmt, db) {
+        Ok((QueryResult::Rows { headers, mut rows }, hints)) => {
+            // 空结果按设置决定是否输出仅含表头的表格
+            if rows.is_empty() && !settings.empty_result_headers {
+                return;
+            }
+            // 千位分组只影响显示，结果本身保持原值
+            if settings.thousands_separator {
+                group_int_columns(&mut rows, &hints.int_columns);
+            }
+            // 计算结果按 .numformat 设置显示
+            if let Some(value) = hints.number {
+                rows[0][0] = format_number(value, &settings.number_format);
+            }
+            *has_output = true;
+            println!("{}\n", format_table(headers, rows));
+        }
+        Ok((QueryResult::Created, _)) => {}
+        Ok((QueryResult::Inserted(count), _)) => {
+            *has_output = true;
+            println!("{} row(s) inserted\n", count);
+        }
+        Ok((QueryResult::Updated(count), _)) => {
+            *has_output = true;
+            println!("{} row(s) updated\n", count);
+        }
+        Ok((QueryResult::Deleted(count), _)) => {
+            *has_output = true;
+            println!("{} row(s) deleted\n", count);
+        }
+        Ok((QueryResult::Dropped(count), _)) => {
+            *has_output = true;
+            println!("Dropped {} table(s)\n", count);
+        }
+        // 计算溢出单独提示，其余解析错误统一报语法错误
+        Err(DbError::Parse(e)) if e == NUMERIC_OVERFLOW => {
+            eprintln!("Error: {}", e);
+            *has_error = true;
+        }
+        // 严格模式下给出具体的语法错误
+        Err(DbError::Parse(e)) if db.strict_mode() => {
+            eprintln!("Error: {}", e);
+            *has_error = true;
+        }
+        Err(DbError::Parse(_)) => {
+            eprintln!("Error: Syntax error");
+            *has_error = true;
+        }
+        // 多条约束违规时一并输出
+        Err(DbError::Violations(list)) if list.len() > 1 => {
+            eprintln!("{}", list.join("; "));
+            *has_error = true;
+        }
+        Err(e @ (DbError::Execution(_) | DbError::Violations(_))) => {
+            // 特殊处理主键重复和非空错误
+            let e = e.to_string();
+            if e.contains("Duplicate entry") {
+                let value = e.split("'").nth(1).unwrap_or("");
+                eprintln!("Error: Duplicate entry '{}' for key 'PRIMARY'", value);
+            } else if e.contains("cannot be null") {
+                let col_name = e.split("'").nth(1).unwrap_or("");
+                eprintln!("Field '{}' doesn't have a default value", col_name);
+            } else {
+                eprintln!("{}", e);
+            }
+            *has_error = true;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            *has_error = true;
+        }
+    }
+}
+
+// 解析并执行一条语句，不打印；解析失败为 DbError::Parse，执行失败为 DbError::Execution
+pub fn run_statement(stmt: &str, db: &mut Database) -> Result<QueryResult, DbError> {
+    run_statement_typed(stmt, db).map(|(result, _)| result)
+}
+
+// 结果的显示信息，只在打印时使用，不影响 QueryResult
+#[derive(Default)]
+struct DisplayHints {
+    int_columns: Vec<bool>, // SELECT 结果每一列是否来自 INT 列
+    number: Option<f64>,    // 计算语句的原始结果
+}
+
+// 同 run_statement，另外返回打印结果时需要的显示信息
+fn run_statement_typed(stmt: &str, db: &mut Database) -> Result<(QueryResult, DisplayHints), DbError> {
+    let ast = db.parse_cached(stmt).map_err(DbError::Parse)?;
+    let result = match ast {
+        select @ SqlAst::Select { .. } => {
+            let (headers, rows, int_columns) = select_rows_typed(db, select)?;
+            return Ok((QueryResult::Rows { headers, rows }, DisplayHints { int_columns, number: None }));
+        }
+        SqlAst::Calculate { expression, result } => {
+            let rows = vec![vec![result.to_string()]];
+            let hints = DisplayHints { int_columns: Vec::new(), number: Some(result) };
+            return Ok((QueryResult::Rows { headers: vec![expression], rows }, hints));
+        }
+        SqlAst::CreateTable { table_name, columns, defaults, comments } => {
+            let col_defs: Vec<(&str, _, bool, bool)> = columns.iter()
+                .map(|(name, dt, pk, nn)| (name.as_str(), dt.clone(), *pk, *nn))
+                .collect();
+            db.create_table(&table_name, col_defs)?;
+            for (col, value) in &defaults {
+                db.set_column_default(&table_name, col, Some(value))?;
+            }
+            for (col, comment) in &comments {
+                db.set_column_comment(&table_name, col, Some(comment))?;
+            }
+            QueryResult::Created
+        }
+        SqlAst::Insert { table, columns, values, returning } => {
+            let values_ref: Vec<Vec<&str>> = values.iter()
+                .map(|row| row.iter().map(|s| s.as_str()).collect())
+                .collect();
+            if returning.is_empty() {
+                QueryResult::Inserted(db.insert(&table, columns, values_ref)?)
+            } else {
+                // RETURNING 的结果按查询结果输出
+                let (columns_ret, aliases): (Vec<String>, Vec<Option<String>>) = returning.into_iter().unzip();
+                let rows = db.insert_returning(&table, columns, values_ref, columns_ret.iter().map(|s| s.as_str()).collect())?;
+                let table_columns: Vec<String> = db.table(&table)
+                    .map(|t| t.columns.iter().map(|c| c.name.clone()).collect())
+                    .unwrap_or_default();
+                QueryResult::Rows { headers: expand_headers(&columns_ret, &aliases, &table_columns), rows }
+            }
+        }
+        SqlAst::Update { table, alias, set, where_clause } => {
+            QueryResult::Updated(db.update_with_alias(&table, alias.as_deref(), set, where_clause.as_deref())?)
+        }
+        SqlAst::Delete { table, alias, where_clause } => {
+            // 删除时的条件错误属于执行错误，不报告为语法错误
+            let count = db.delete_with_alias(&table, alias.as_deref(), where_clause.as_deref())
+                .map_err(|e| DbError::Execution(e.to_string()))?;
+            QueryResult::Deleted(count)
+        }
+        SqlAst::Drop { tables, if_exists } => QueryResult::Dropped(db.drop_tables(&tables, if_exists)?),
+        SqlAst::Analyze { table } => {
+            let stats = db.analyze(&table)?;
+            QueryResult::Rows {
+                headers: ["column", "rows", "nulls", "distinct"].map(String::from).to_vec(),
+                rows: stats.into_iter().map(|s| vec![
+                    s.column,
+                    s.total.to_string(),
+                    s.nulls.to_string(),
+                    s.distinct.to_string(),
+                ]).collect(),
+            }
+        }
+        SqlAst::Explain { query } => {
+            let plan = db.explain(&query)?;
+            QueryResult::Rows {
+                headers: ["step", "operation", "detail"].map(String::from).to_vec(),
+                rows: plan.into_iter().enumerate().map(|(i, step)| vec![
+                    (i + 1).to_string(),
+                    step.operation.to_string(),
+                    step.detail,
+                ]).collect(),
+            }
+        }
+        SqlAst::Describe { table } => QueryResult::Rows {
+            headers: ["column", "type", "key", "null", "default", "comment"].map(String::from).to_vec(),
+            rows: db.describe(&table)?,
+        },
+        SqlAst::Optimize { table } => {
+            let stats = db.optimize_table(&table)?;
+            QueryResult::Rows {
+                headers: ["table", "rows", "index_entries"].map(String::from).to_vec(),
+                rows: vec![vec![
+                    stats.table,
+                    stats.rows.to_string(),
+                    stats.index_entries.to_string(),
+                ]],
+            }
+        }
+    };
+    Ok((result, DisplayHints::default()))
+}
+
+// 执行 SELECT 并格式化结果；无结果时按设置输出仅含表头的表格或返回 None
+pub fn format_select(db: &Database, select: SqlAst, settings: &ReplSettings) -> Result<Option<String>, String> {
+    let (headers, rows) = select_rows(db, select)?;
+    if rows.is_empty() && !settings.empty_result_headers {
+        return Ok(None); // 空结果不输出
+    }
+    Ok(Some(format_table(headers, rows)))
+}
+
+// 执行 SELECT，返回表头和结果行
+pub fn select_rows(db: &Database, select: SqlAst) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    select_rows_typed(db, select).map(|(headers, rows, _)| (headers, rows))
+}
+
+// 表头、结果行，以及每一列是否来自 INT 列
+type TypedRows = (Vec<String>, Vec<Vec<String>>, Vec<bool>);
+
+// 同 select_rows，另外返回每一列是否来自 INT 列
+fn select_rows_typed(db: &Database, select: SqlAst) -> Result<TypedRows, String> {
+    let SqlAst::Select { table, alias, joins, columns, aliases, where_clause, group_by, order_by, limit, offset, distinct_on } = select else {
+        return Err("Not a SELECT statement".to_string());
+    };
+    let mut cols_ref: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
+    // DISTINCT ON：在结果末尾附带键列，排序后去重，最后才应用 OFFSET/LIMIT
+    let (query_limit, query_offset) = if distinct_on.is_empty() { (limit, offset) } else { (None, None) };
+    cols_ref.extend(distinct_on.iter().map(|s| s.as_str()));
+    let cond_str = where_clause.as_deref();
+    let group_by_ref: Vec<&str> = group_by.iter().map(|s| s.as_str()).collect();
+    let order_by_ref = order_by.iter()
+        .map(|(col, desc)| (col.as_str(), *desc))
+        .collect::<Vec<_>>();
+
+    // 无连接且无表别名时直接查询原表，否则先构造带限定列名的表
+    if joins.is_empty() && alias.is_none() {
+        let data = db.select_grouped(&table, cols_ref, cond_str, group_by_ref, Some(order_by_ref), query_limit, query_offset)?;
+        let data = keep_first_per_key(data, distinct_on.len(), limit, offset);
+        let is_int = db.table(&table).map(|t| int_columns(t, &columns)).unwrap_or_default();
+        let table_columns: Vec<String> = db.table(&table)
+            .map(|t| t.columns.iter().map(|c| c.name.clone()).collect())
+            .unwrap_or_default();
+        return Ok((expand_headers(&columns, &aliases, &table_columns), data, is_int));
+    }
+
+    let joined = db.joined_table(&table, alias.as_deref(), &joins)?;
+    let data = db.select_grouped_from(&joined, cols_ref, cond_str, group_by_ref, Some(order_by_ref), query_limit, query_offset)?;
+    let data = keep_first_per_key(data, distinct_on.len(), limit, offset);
+    let is_int = int_columns(&joined, &columns);
+    // * 展开时表头使用不带限定符的列名
+    let table_columns: Vec<String> = joined.columns.iter()
+        .map(|c| c.name.rsplit_once('.').map_or(c.name.as_str(), |(_, n)| n).to_string())
+        .collect();
+    Ok((expand_headers(&columns, &aliases, &table_columns), data, is_int))
+}
+
+// DISTINCT ON：每行末尾的 key_count 个值是键，按顺序每个键只保留第一行并去掉键列
+fn keep_first_per_key(
+    rows: Vec<Vec<String>>,
+    key_count: usize,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Vec<Vec<String>> {
+    if key_count == 0 {
+        return rows;
+    }
+    let mut seen = std::collections::HashSet::new();
+    rows.into_iter()
+        .filter_map(|mut row| {
+            let key = row.split_off(row.len() - key_count);
+            seen.insert(key).then_some(row)
+        })
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+// 结果表头：* 在所在位置展开为表的所有列名，其余列有别名时使用别名
+fn expand_headers(columns: &[String], aliases: &[Option<String>], table_columns: &[String]) -> Vec<String> {
+    let mut headers = Vec::with_capacity(columns.len());
+    for (col, alias) in columns.iter().zip(aliases) {
+        if col == "*" {
+            headers.extend(table_columns.iter().cloned());
+        } else {
+            headers.push(alias.as_deref().unwrap_or(col).to_string());
+        }
+    }
+    headers
+}
@@ -1,10 +1,15 @@
 use std::io::{self, Write};
-use rustique_db::database::{Database, DataType};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use rustique_db::database::{Database, DataType, DEFAULT_HISTORY_PATH};
 use rustique_db::format::format_table;
 use rustique_db::format::format_table_from_db;
 use rustique_db::parser::{parse_sql, SqlAst};
 use rustique_db::history::CommandHistory;
-use rustique_db::execute_sql;
+use rustique_db::repl::{self, InputBuffer, ReplSettings};
+use rustique_db::split_statements;
 
 // 注释处理
 fn remove_comments(input: &str) -> &str {
@@ -45,8 +50,8 @@ fn remove_comments(input: &str) -> &str {
 }
 
 // 带历史支持的输入读取
-fn read_input_with_history(prompt: &str, history: &mut CommandHistory) -> String {
-    let mut input = String::new();
+fn read_input_with_history(prompt: &str, history: &mut CommandHistory, interrupted: &AtomicBool) -> String {
+    let mut input = InputBuffer::default();
     let mut is_multiline = false;
 
     loop {
@@ -56,20 +61,25 @@ fn read_input_with_history(prompt: &str, history: &mut CommandHistory) -> String
         let mut line = String::new();
         io::stdin().read_line(&mut line).unwrap();
 
+        // Ctrl-C 后读到的第一行（通常是空行）结束输入，放弃未完成的命令，由主循环保存并退出
+        if interrupted.load(Ordering::SeqCst) {
+            return String::new();
+        }
+
         // 处理历史命令导航（仅在第一行）
         if !is_multiline {
             match line.trim_end() {
                 "\x1b[A" => { // 上箭头
                     if let Some(cmd) = history.get_previous() {
-                        input = cmd.to_string();
-                        print!("\r\x1b[K{}{}", prompt, input);
+                        input.replace(cmd);
+                        print!("\r\x1b[K{}{}", prompt, input.as_str());
                         continue;
                     }
                 }
                 "\x1b[B" => { // 下箭头
                     if let Some(cmd) = history.get_next() {
-                        input = cmd.to_string();
-                        print!("\r\x1b[K{}{}", prompt, input);
+                        input.replace(cmd);
+                        print!("\r\x1b[K{}{}", prompt, input.as_str());
                         continue;
                     }
                 }
@@ -77,18 +87,22 @@ fn read_input_with_history(prompt: &str, history: &mut CommandHistory) -> String
             }
         }
 
-        input.push_str(&line);
+        // \c 或连续两个空行：放弃当前输入，回到新的提示符
+        if input.push_line(&line) {
+            is_multiline = false;
+            continue;
+        }
 
-        // 检查结束条件（分号或exit）
-        let trimmed = input.trim();
-        if trimmed.ends_with(';') || trimmed.eq_ignore_ascii_case("exit") {
+        // 检查结束条件（分号、exit 或单行的 . 命令）
+        let trimmed = input.as_str().trim();
+        if trimmed.ends_with(';') || trimmed.eq_ignore_ascii_case("exit") || repl::is_dot_command(trimmed) {
             break;
         }
 
         is_multiline = true;
     }
 
-    input.trim().to_string()
+    input.as_str().trim().to_string()
 }
 
 fn should_exit(input: &str) -> bool {
@@ -96,34 +110,127 @@ fn should_exit(input: &str) -> bool {
     trimmed == "exit" || trimmed == "exit;" || trimmed == "quit" || trimmed == "quit;"
 }
 
+// 询问用户确认，只有输入 y/yes 时返回 true
+fn confirm(prompt: &str) -> bool {
+    print!("{} (y/N) ", prompt);
+    io::stdout().flush().unwrap();
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
 fn clean_command_arg(input: &str) -> &str {
     input.trim().trim_end_matches(';').trim()
 }
 
+// 不区分大小写地判断命令前缀；按字节切片前先确认边界，输入含多字节字符时不会 panic
+fn has_command_prefix(cmd: &str, prefix: &str) -> bool {
+    cmd.len() > prefix.len() && cmd.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+}
+
+// 执行前检查不带 WHERE 的 DELETE/UPDATE，未确认时不执行
+fn run_guarded(input: &str, db: &mut Database, settings: &ReplSettings) {
+    if !settings.force {
+        for stmt in split_statements(input) {
+            let Ok(ast) = parse_sql(&stmt) else { continue };
+            if let Some(table) = repl::affects_all_rows(&ast) {
+                let rows = db.row_count(table).unwrap_or(0);
+                if !confirm(&format!("This will affect all {} rows. Continue?", rows)) {
+                    println!("Cancelled");
+                    return;
+                }
+            }
+        }
+    }
+    let start = Instant::now();
+    let _ = repl::run_input(input, db, settings);
+    if settings.timing {
+        println!("{}", repl::format_elapsed(start.elapsed()));
+    }
+}
+
 fn main() {
     let mut history = CommandHistory::new(100);
-    let mut db = Database::load_with_history(&mut history).unwrap_or_else(|_| {
-        println!("Creating new database...");
-        Database::new()
-    });
+    let mut settings = ReplSettings::default();
+    // --strict-load：db.json 中有与列类型不符的值时拒绝启动，而不是照常加载
+    let strict_load = std::env::args().skip(1).any(|arg| arg == "--strict-load");
+    let loaded = if strict_load {
+        Database::load_strict_with_history(&mut history)
+    } else {
+        Database::load_with_history(&mut history)
+    };
+    let mut db = match loaded {
+        Ok(db) => {
+            // 与列类型不符的值按文本保留，只提示不拒绝加载
+            let mismatches = db.type_mismatches();
+            if let Some(first) = mismatches.first() {
+                eprintln!("Warning: {} value(s) do not match their column type and were kept as text ({})",
+                    mismatches.len(), first);
+            }
+            db
+        }
+        // 被其他进程锁定、文件无法读取或格式错误时直接退出：
+        // 用空库顶替会在退出时覆盖原文件（文件不存在时 load 本身就返回空库）
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Ctrl-C 只设置标志，由主循环保存后退出，避免丢失未写盘的修改
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&interrupted);
+    if let Err(e) = ctrlc::set_handler(move || {
+        flag.store(true, Ordering::SeqCst);
+        eprintln!("\nInterrupted, press Enter to save and exit (unfinished input is discarded)");
+    }) {
+        eprintln!("Warning: Failed to install Ctrl-C handler: {}", e);
+    }
 
     println!("Welcome to RustiqueDB!");
-    println!("Database loaded with {} tables", db.tables.len());
+    println!("Database loaded with {} tables", db.tables_iter().count());
     
     println!("Enter SQL commands (type 'exit' to quit, use ; to end commands):");
 
     println!("Special commands:");
     println!("  !!;       - 重复上一条命令");
     println!("  !n;       - 执行历史记录中第n条命令");
+    println!("  !prefix;  - 执行最近一条以 prefix 开头的命令");
     println!("  HISTORY;  - 显示所有历史命令");
+    println!("  HISTORY SEARCH term; - 搜索包含 term 的历史命令");
     println!("  CLEAR;    - 清空历史记录");
+    println!("  SET HISTORY SIZE n; - 设置历史记录上限");
+    println!("  DUMP;     - 输出重建数据库的 SQL 语句");
+    println!("  CHECK;    - 检查所有表的数据完整性");
+    println!("  PEEK table; - 显示表的前 5 行");
+    println!("  SOURCE file.sql; - 执行 SQL 文件");
+    println!("  RESET;    - 删除所有表（需确认）");
+    println!("  DROP DATABASE; - 删除所有表和数据库文件（需确认）");
+    println!("  \\c        - 放弃正在输入的多行命令（也可以连续输入两个空行）");
+    println!("  .autosave on|off - 开启/关闭每条语句后的自动保存");
+    println!("  .save            - 立即保存数据库");
+    println!("  .schema [table]  - 输出表的 CREATE TABLE 语句");
+    println!("  .load table      - 从标准输入逐行读取逗号分隔值插入表，以 \\. 结束");
+    println!("  .force on|off    - 不带 WHERE 的 DELETE/UPDATE 不再确认");
+    println!("  .headers on|off  - 查询无结果时仍显示表头");
+    println!("  .readonly on|off - 只读模式，禁止修改和保存");
+    println!("  .timing on|off   - 显示每次执行的耗时");
+    println!("  .compact on|off  - 以紧凑 JSON 保存数据库");
+    println!("  .strict on|off   - 拼错的 SQL 报语法错误而不是当作计算");
+    println!("  .trim on|off     - 写入 VARCHAR 值时去掉首尾空白");
+    println!("  .thousands on|off - 查询结果中的 INT 列按千位分组显示");
+    println!("  .progress on|off - UPDATE/DELETE/CSV 导入每 10000 行输出一次进度");
+    println!("  .numformat plain|sci [n] - 计算结果的显示方式（科学计数法、n 位小数）");
     
     loop {
-        let input = read_input_with_history("sql> ", &mut history);
+        let input = read_input_with_history("sql> ", &mut history, &interrupted);
 
-        if should_exit(&input) {
-            if let Err(e) = db.save() {
-                eprintln!("Failed to save database: {}", e);
+        if should_exit(&input) || interrupted.load(Ordering::SeqCst) {
+            if let Err(e) = repl::save_on_shutdown(&db, &history, Path::new(DEFAULT_HISTORY_PATH)) {
+                eprintln!("{}", e);
             }
             println!("Goodbye!");
             break;
@@ -131,6 +238,11 @@ fn main() {
 
         let trimmed = input.trim();
 
+        if repl::is_dot_command(trimmed) {
+            repl::handle_dot_command(trimmed, &mut db, &mut settings);
+            continue;
+        }
+
         // 特殊命令处理
         match trimmed {
             "HISTORY" | "HISTORY;" => {
@@ -139,6 +251,28 @@ fn main() {
                 }
                 continue;
             },
+            cmd if has_command_prefix(cmd, "HISTORY SEARCH ") => {
+                let term = clean_command_arg(&cmd[15..]);
+                let matches = history.search(term);
+                if matches.is_empty() {
+                    println!("No history entries matching '{}'", term);
+                }
+                for (i, cmd) in matches {
+                    println!("{:4}: {}", i, cmd.trim());
+                }
+                continue;
+            },
+            cmd if has_command_prefix(cmd, "SET HISTORY SIZE ") => {
+                let arg = clean_command_arg(&cmd[17..]);
+                match arg.parse::<usize>() {
+                    Ok(n) if n > 0 => {
+                        history.set_max_size(n);
+                        println!("History size set to {}", n);
+                    }
+                    _ => eprintln!("Error: Invalid history size '{}'", arg),
+                }
+                continue;
+            },
             "CLEAR" | "CLEAR;" => {
                 history.clear();
                 println!("Command history cleared");
@@ -147,7 +281,7 @@ fn main() {
             "!!" | "!!;" => {
                 if let Some(last) = history.get_full_command(history.len().saturating_sub(1)) {
                     println!("Re-executing: {}", last.trim());
-                    let _ = execute_sql(&last, &mut db, &mut history);
+                    run_guarded(&last, &mut db, &settings);
                 }
                 continue;
             },
@@ -156,12 +290,15 @@ fn main() {
                 if let Ok(n) = arg.parse::<usize>() {
                     if let Some(cmd) = history.get_full_command(n) {
                         println!("Executing #{}: {}", n, cmd.trim());
-                        let _ = execute_sql(&cmd, &mut db, &mut history);
+                        run_guarded(&cmd, &mut db, &settings);
                     } else {
                         eprintln!("Error: No history entry at index {}", n);
                     }
+                } else if let Some((n, cmd)) = history.find_last_starting_with(arg) {
+                    println!("Executing #{}: {}", n, cmd.trim());
+                    run_guarded(&cmd, &mut db, &settings);
                 } else {
-                    eprintln!("Error: Invalid history index '{}'", arg);
+                    eprintln!("Error: No history entry starting with '{}'", arg);
                 }
                 continue;
             },
@@ -172,6 +309,59 @@ fn main() {
                 }
                 continue;
             },
+            cmd if has_command_prefix(cmd, "SOURCE ") => {
+                let path = clean_command_arg(&cmd[7..]).trim_matches(|c| c == '\'' || c == '"');
+                match repl::source_file(Path::new(path), &mut db, &settings) {
+                    Ok((ok, failed)) => println!("Sourced {}: {} succeeded, {} failed", path, ok, failed),
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+                continue;
+            },
+            cmd if has_command_prefix(cmd, "PEEK ") => {
+                match repl::peek(&db, clean_command_arg(&cmd[5..])) {
+                    Ok(table) => println!("{}\n", table),
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+                continue;
+            },
+            "CHECK" | "CHECK;" => {
+                match db.validate() {
+                    Ok(()) => println!("OK: no integrity problems found"),
+                    Err(errors) => {
+                        for e in &errors {
+                            eprintln!("{}", e);
+                        }
+                        eprintln!("{} integrity problem(s) found", errors.len());
+                    }
+                }
+                continue;
+            },
+            "DUMP" | "DUMP;" => {
+                print!("{}", db.to_sql());
+                continue;
+            },
+            "RESET" | "RESET;" => {
+                if confirm("This will delete all tables. Continue?") {
+                    match db.clear_all().map_err(String::from).and_then(|_| db.save()) {
+                        Ok(_) => println!("Database reset"),
+                        Err(e) => eprintln!("Failed to save database: {}", e),
+                    }
+                } else {
+                    println!("Reset cancelled");
+                }
+                continue;
+            },
+            cmd if cmd.trim_end_matches(';').eq_ignore_ascii_case("DROP DATABASE") => {
+                if confirm(&format!("This will delete all tables and {}. Continue?", db.path().display())) {
+                    match db.drop_all() {
+                        Ok(()) => println!("Database dropped"),
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
+                } else {
+                    println!("Drop cancelled");
+                }
+                continue;
+            },
             "SNAPSHOT" | "SNAPSHOT;" => {
                 db.take_snapshot();
                 println!("Snapshot taken (count: {})", db.snapshots.len());
@@ -182,7 +372,7 @@ fn main() {
 
         if !trimmed.is_empty() {
             history.add(&input);
-            let _ = execute_sql(trimmed, &mut db, &mut history);
+            run_guarded(trimmed, &mut db, &settings);
         }
     }
 }
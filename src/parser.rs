@@ -1,17 +1,26 @@
 use crate::database::DataType as DbDataType;
+use crate::database::{Join as DbJoin, JoinCondition, JoinKind, DEFAULT_VALUE, NULLS_FIRST, NULLS_LAST, RANDOM_ORDER_KEY, SET_EXPRESSION_PREFIX};
 use sqlparser::{
     ast::*,
     dialect::GenericDialect,
     parser::Parser,
 };
+use std::sync::LazyLock;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SqlAst {
     Select {
         table: String,
+        alias: Option<String>,         // FROM 表的别名
+        joins: Vec<DbJoin>,            // JOIN 子句（按出现顺序）
         columns: Vec<String>,
+        aliases: Vec<Option<String>>,  // 与 columns 一一对应的别名
         where_clause: Option<String>,
+        group_by: Vec<String>,         // GROUP BY 列
         order_by: Vec<(String, bool)>,
+        limit: Option<usize>,  // LIMIT 行数
+        offset: Option<usize>, // OFFSET 跳过的行数
+        distinct_on: Vec<String>,  // DISTINCT ON 的键列，排序后每个键只保留第一行
     },
     Calculate {
         expression: String,  // 原始表达式
@@ -20,30 +29,51 @@ pub enum SqlAst {
     CreateTable {
         table_name: String,
         columns: Vec<(String, DbDataType, bool, bool)>,
+        defaults: Vec<(String, String)>,  // (列名, 默认值)
+        comments: Vec<(String, String)>,  // (列名, 注释)
     },
     Insert {
         table: String,
         columns: Option<Vec<String>>, // 新增：可选列名列表
         values: Vec<Vec<String>>,  // 修改为支持多行
+        returning: Vec<(String, Option<String>)>,  // RETURNING 的列和别名，为空表示没有 RETURNING
     },
     Update {
         table: String,
+        alias: Option<String>,  // 表别名，WHERE 和 SET 中可以用 别名.列
         set: Vec<(String, String)>,
         where_clause: Option<String>,
     },
     Delete {
         table: String,
+        alias: Option<String>,
         where_clause: Option<String>,
     },
     Drop {
         tables: Vec<String>,
         if_exists: bool,  // 保留此字段
     },
+    Analyze {
+        table: String,
+    },
+    Optimize {
+        table: String,
+    },
+    Explain {
+        query: Box<SqlAst>,  // 只支持 SELECT
+    },
+    Describe {
+        table: String,
+    },
 }
 
+// 整数除法运算符（// 或 DIV），向零截断
+const INT_DIV: char = '\\';
+
 const OPERATOR_PRECEDENCE: &[(char, u8)] = &[
     ('*', 3),
     ('/', 3),
+    (INT_DIV, 3),
     ('+', 2),
     ('-', 2),
 ];
@@ -64,61 +94,133 @@ enum Token {
     RightParen,
 }
 
+fn flush_number(num_buffer: &mut String, tokens: &mut Vec<Token>) -> Result<(), String> {
+    if !num_buffer.is_empty() {
+        tokens.push(Token::Number(num_buffer.parse().map_err(|_| "Invalid number")?));
+        num_buffer.clear();
+    }
+    Ok(())
+}
+
 // 分词函数
 fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
     let mut tokens = Vec::new();
     let mut num_buffer = String::new();
+    let mut chars = expr.chars().peekable();
 
-    for c in expr.chars() {
+    while let Some(c) = chars.next() {
         match c {
             '0'..='9' | '.' => num_buffer.push(c),
             '+' | '-' | '*' | '/' | '(' | ')' => {
-                if !num_buffer.is_empty() {
-                    tokens.push(Token::Number(num_buffer.parse().map_err(|_| "Invalid number")?));
-                    num_buffer.clear();
-                }
+                flush_number(&mut num_buffer, &mut tokens)?;
                 match c {
                     '(' => tokens.push(Token::LeftParen),
                     ')' => tokens.push(Token::RightParen),
+                    '/' if chars.peek() == Some(&'/') => {
+                        chars.next();
+                        tokens.push(Token::Operator(INT_DIV));
+                    }
                     op => tokens.push(Token::Operator(op)),
                 }
             },
+            // DIV 关键字（不区分大小写）
+            c if c.is_ascii_alphabetic() => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !next.is_ascii_alphabetic() {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                if !word.eq_ignore_ascii_case("DIV") {
+                    return Err(format!("Unknown character: {}", c));
+                }
+                flush_number(&mut num_buffer, &mut tokens)?;
+                tokens.push(Token::Operator(INT_DIV));
+            },
             ' ' => continue,  // 忽略空格
             _ => return Err(format!("Unknown character: {}", c)),
         }
     }
 
     // 处理最后一个数字
-    if !num_buffer.is_empty() {
-        tokens.push(Token::Number(num_buffer.parse().map_err(|_| "Invalid number")?));
+    flush_number(&mut num_buffer, &mut tokens)?;
+
+    // 超出 f64 范围的字面量同样视为溢出
+    if tokens.iter().any(|t| matches!(t, Token::Number(n) if !n.is_finite())) {
+        return Err(NUMERIC_OVERFLOW.into());
     }
 
     Ok(tokens)
 }
 
+// 计算结果溢出（inf/NaN）时的错误信息
+pub const NUMERIC_OVERFLOW: &str = "numeric overflow";
+
 // 运算符应用函数
 fn apply_operator(op: char, left: f64, right: f64) -> Result<f64, String> {
-    match op {
-        '+' => Ok(left + right),
-        '-' => Ok(left - right),
-        '*' => Ok(left * right),
+    let result = match op {
+        '+' => left + right,
+        '-' => left - right,
+        '*' => left * right,
         '/' => {
             if right == 0.0 {
-                Err("Division by zero".into())
-            } else {
-                Ok(left / right)
+                return Err("Division by zero".into());
             }
+            left / right
         },
-        _ => Err(format!("Unknown operator: {}", op))
+        INT_DIV => {
+            if right == 0.0 {
+                return Err("Division by zero".into());
+            }
+            (left / right).trunc()
+        },
+        _ => return Err(format!("Unknown operator: {}", op))
+    };
+
+    // 每一步都检查结果，避免输出 inf 或 NaN
+    if result.is_finite() {
+        Ok(result)
+    } else {
+        Err(NUMERIC_OVERFLOW.into())
     }
 }
 
 pub fn parse_sql(input: &str) -> Result<SqlAst, String> {
+    parse_sql_with(input, false)
+}
+
+// strict 为 true 时，以关键字开头的语句解析失败或不受支持时直接报错，不再当作计算表达式
+pub fn parse_sql_with(input: &str, strict: bool) -> Result<SqlAst, String> {
+    // SELECT 1+1 与 1+1 等价：都按计算处理，表头为原样书写的表达式
+    if let Some(expr) = arithmetic_expression(input) {
+        return parse_calculation(expr);
+    }
+    // EXPLAIN SELECT ...：解析后面的查询，只描述计划不执行
+    if let Some(query) = explained_query(input) {
+        return match parse_sql_with(query, strict)? {
+            select @ SqlAst::Select { .. } => Ok(SqlAst::Explain { query: Box::new(select) }),
+            _ => Err("EXPLAIN only supports SELECT".into()),
+        };
+    }
+    // ANALYZE [TABLE] name：sqlparser 只支持 Hive 风格，这里单独处理
+    if let Some(table) = parse_analyze(input) {
+        return Ok(SqlAst::Analyze { table });
+    }
+    if let Some(table) = parse_optimize(input) {
+        return Ok(SqlAst::Optimize { table });
+    }
+    if let Some(table) = parse_describe(input) {
+        return Ok(SqlAst::Describe { table });
+    }
+
     let dialect = GenericDialect {};
     let mut parser = Parser::new(&dialect);
+    let prepared = normalize_quoting(input);
     
     // 首先尝试解析为常规SQL语句
-    match parser.try_with_sql(input)
+    match parser.try_with_sql(&prepared)
         .map_err(|e| e.to_string())
         .and_then(|mut p| p.parse_statement().map_err(|e| e.to_string()))
     {
@@ -131,8 +233,8 @@ pub fn parse_sql(input: &str) -> Result<SqlAst, String> {
                 Statement::CreateTable { name, columns, constraints, .. } => {
                     parse_create_table(name, columns, constraints)
                 }
-                Statement::Insert { table_name, columns, source, .. } => {
-                    parse_insert(table_name, columns, source)
+                Statement::Insert { table_name, columns, source, returning, .. } => {
+                    parse_insert(table_name, columns, source, returning)
                 }
                 Statement::Update { table, assignments, selection, .. } => {
                     parse_update(table, assignments, selection)
@@ -148,13 +250,144 @@ pub fn parse_sql(input: &str) -> Result<SqlAst, String> {
                 if object_type == ObjectType::Table => {
                     parse_drop_table(names, if_exists)
                 }
+                _ if strict && looks_like_sql(input) => {
+                    Err(format!("Unsupported statement: {}", leading_keyword(input)))
+                }
                 _ => parse_calculation(input.trim()) // 如果不是支持的SQL语句，尝试解析为计算表达式
             }
         },
-        Err(_) => parse_calculation(input.trim()) // 如果解析失败，尝试解析为计算表达式
+        Err(e) => {
+            // sqlparser 不接受 LIMIT offset, count 时改写为 LIMIT count OFFSET offset 再解析
+            if let Some(rewritten) = rewrite_mysql_limit(input) {
+                return parse_sql_with(&rewritten, strict);
+            }
+            // 部分方言允许省略 FROM：DELETE t WHERE ...
+            if let Some(rewritten) = rewrite_delete_without_from(input) {
+                return parse_sql_with(&rewritten, strict);
+            }
+            if strict && looks_like_sql(input) {
+                let keyword = leading_keyword(input);
+                return Err(if SQL_KEYWORDS.contains(&keyword.as_str()) {
+                    format!("Syntax error in {} statement: {}", keyword, e)
+                } else {
+                    format!("Syntax error: unknown statement '{}'", keyword)
+                });
+            }
+            parse_calculation(input.trim()) // 如果解析失败，尝试解析为计算表达式
+        }
+    }
+}
+
+// 严格模式下识别的语句关键字
+const SQL_KEYWORDS: &[&str] = &[
+    "SELECT", "INSERT", "UPDATE", "DELETE", "CREATE", "DROP", "ALTER",
+    "ANALYZE", "OPTIMIZE", "WITH", "EXPLAIN", "TRUNCATE", "GRANT", "SHOW",
+];
+
+// 语句开头的单词（大写）
+fn leading_keyword(input: &str) -> String {
+    input.trim_start()
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or("")
+        .to_uppercase()
+}
+
+// 以单词开头的输入视为 SQL（纯计算已在解析前处理）
+fn looks_like_sql(input: &str) -> bool {
+    !leading_keyword(input).is_empty()
+}
+
+// 去掉可选的 SELECT 前缀和结尾分号后，只含数字和运算符时返回该表达式
+fn arithmetic_expression(input: &str) -> Option<&str> {
+    let input = input.trim();
+    let expr = match input.get(..7) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("SELECT ") => &input[7..],
+        _ => input,
+    };
+    let expr = expr.trim_end_matches(|c: char| c == ';' || c.is_whitespace()).trim();
+    let is_arithmetic = !expr.is_empty()
+        && expr.chars().any(|c| c.is_ascii_digit())
+        && expr.chars().all(|c| c.is_ascii_digit() || " \t.+-*/()".contains(c));
+    is_arithmetic.then_some(expr)
+}
+
+static EXPLAIN_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"(?is)^\s*EXPLAIN\s+(.+)$").unwrap());
+
+// EXPLAIN 之后的语句
+fn explained_query(input: &str) -> Option<&str> {
+    EXPLAIN_RE.captures(input).map(|caps| caps.get(1).unwrap().as_str())
+}
+
+static ANALYZE_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r#"(?i)^\s*ANALYZE\s+(?:TABLE\s+)?("[^"]*"|`[^`]*`|[\w.]+)\s*;?\s*$"#).unwrap());
+
+fn parse_analyze(input: &str) -> Option<String> {
+    ANALYZE_RE.captures(input).map(|caps| clean_identifier(&caps[1]))
+}
+
+static OPTIMIZE_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r#"(?i)^\s*OPTIMIZE\s+TABLE\s+("[^"]*"|`[^`]*`|[\w.]+)\s*;?\s*$"#).unwrap());
+
+// OPTIMIZE TABLE name
+fn parse_optimize(input: &str) -> Option<String> {
+    OPTIMIZE_RE.captures(input).map(|caps| clean_identifier(&caps[1]))
+}
+
+static DESCRIBE_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r#"(?i)^\s*DESC(?:RIBE)?\s+("[^"]*"|`[^`]*`|[\w.]+)\s*;?\s*$"#).unwrap());
+
+// DESCRIBE name 或 DESC name
+fn parse_describe(input: &str) -> Option<String> {
+    DESCRIBE_RE.captures(input).map(|caps| clean_identifier(&caps[1]))
+}
+
+// GenericDialect 不识别 MySQL 的反引号标识符和字符串中的 \'：
+// `name` 改写为 "name"，\' 改写为 \''（由 sqlparser 还原成 \' 后再交给 unescape_string_literal）
+fn normalize_quoting(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    let mut quote: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (Some('\''), '\\') => {
+                result.push(c);
+                match chars.next() {
+                    Some('\'') => result.push_str("''"),
+                    Some(next) => result.push(next),
+                    None => {}
+                }
+                continue;
+            }
+            // 反引号内的双引号需要写成 ""
+            (Some('`'), '"') => result.push('"'),
+            _ => {}
+        }
+        result.push(if c == '`' && matches!(quote, None | Some('`')) { '"' } else { c });
+    }
+    result
+}
+
+static MYSQL_LIMIT_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"(?i)\bLIMIT\s+(\d+)\s*,\s*(\d+)").unwrap());
+
+// 将 MySQL 风格的 LIMIT offset, count 改写为标准写法
+fn rewrite_mysql_limit(input: &str) -> Option<String> {
+    if !MYSQL_LIMIT_RE.is_match(input) {
+        return None;
     }
+    Some(MYSQL_LIMIT_RE.replace(input, "LIMIT $2 OFFSET $1").into_owned())
 }
 
+static DELETE_WITHOUT_FROM_RE: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"(?is)^\s*DELETE\s+(\S+)(.*)$").unwrap());
+
+// 将 DELETE t ... 改写为 DELETE FROM t ...
+fn rewrite_delete_without_from(input: &str) -> Option<String> {
+    let caps = DELETE_WITHOUT_FROM_RE.captures(input)?;
+    if caps[1].eq_ignore_ascii_case("FROM") {
+        return None;
+    }
+    Some(format!("DELETE FROM {}{}", &caps[1], &caps[2]))
+}
 
 fn parse_select(query: &Query) -> Result<SqlAst, String> {
     match query.body.as_ref() {
@@ -169,58 +402,243 @@ fn parse_select(query: &Query) -> Result<SqlAst, String> {
                 return Err("Calculation expressions must have exactly one column".into());
             }
 
-            let table = select
-                .from
-                .first()
-                .and_then(|t| match &t.relation {
-                    TableFactor::Table { name, .. } => Some(name.to_string()),
-                    _ => None,
-                })
-                .ok_or("Missing table name in FROM clause")?;
+            let from = select.from.first().ok_or("Missing table name in FROM clause")?;
+            let (table, alias) = parse_table_factor(&from.relation)?;
+            let joins = from.joins.iter().map(parse_join).collect::<Result<Vec<_>, _>>()?;
 
-            let columns = select
+            let (columns, aliases): (Vec<String>, Vec<Option<String>>) = select
                 .projection
                 .iter()
-                .map(|p| match p {
-                    SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Ok(ident.value.clone()),
-                    SelectItem::Wildcard(_) => Ok("*".to_string()),
-                    _ => Err("Unsupported column expression".to_string()),
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+                .map(parse_select_item)
+                .collect::<Result<Vec<_>, String>>()?
+                .into_iter()
+                .unzip();
 
             let where_clause = select
                 .selection
                 .as_ref()
                 .map(|expr| expr.to_string());
 
+            let distinct_on = match &select.distinct {
+                Some(Distinct::On(exprs)) => exprs.iter()
+                    .map(|expr| match expr {
+                        Expr::Identifier(ident) => Ok(ident.value.clone()),
+                        Expr::CompoundIdentifier(idents) => Ok(compound_identifier_to_string(idents)),
+                        _ => Err("Only column names are supported in DISTINCT ON".to_string()),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                _ => Vec::new(),
+            };
+
+            let group_by = select.group_by.iter()
+                .map(|expr| match expr {
+                    Expr::Identifier(ident) => Ok(ident.value.clone()),
+                    Expr::CompoundIdentifier(idents) => Ok(compound_identifier_to_string(idents)),
+                    _ => Err("Only column names are supported in GROUP BY".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // ASC/DESC 关键字由 sqlparser 解析，大小写不敏感
             let mut order_by = Vec::new();
             for expr in &query.order_by {  // 直接迭代&Vec
                 match &expr.expr {
+                    // 结果列别名优先，替换为对应的表达式（例如 COUNT(*) AS c 中的 c）
+                    Expr::Identifier(ident) if ident.quote_style.is_none() => {
+                        let column = aliases.iter()
+                            .position(|a| a.as_ref().is_some_and(|a| a.eq_ignore_ascii_case(&ident.value)))
+                            .map_or_else(|| ident.value.clone(), |pos| columns[pos].clone());
+                        order_by.push((column, !expr.asc.unwrap_or(true)));
+                    },
                     Expr::Identifier(ident) => {
                         order_by.push((ident.value.clone(), !expr.asc.unwrap_or(true)));
                     },
+                    Expr::CompoundIdentifier(idents) => {
+                        order_by.push((compound_identifier_to_string(idents), !expr.asc.unwrap_or(true)));
+                    },
+                    // ORDER BY RANDOM() / RAND() 用于随机抽样
+                    Expr::Function(func) if func.args.is_empty() && is_random_function(&func.name) => {
+                        order_by.push((RANDOM_ORDER_KEY.to_string(), false));
+                    },
+                    // 按聚合结果排序，例如 ORDER BY COUNT(*) DESC
+                    Expr::Function(_) => {
+                        order_by.push((expr.expr.to_string(), !expr.asc.unwrap_or(true)));
+                    },
                     _ => return Err("Only column names are supported in ORDER BY".into()),
                 }
+                // NULLS FIRST / NULLS LAST 以后缀形式附在列名后
+                if let (Some(nulls_first), Some((column, _))) = (expr.nulls_first, order_by.last_mut())
+                    && column != RANDOM_ORDER_KEY
+                {
+                    column.push_str(if nulls_first { NULLS_FIRST } else { NULLS_LAST });
+                }
             }
 
+            // LIMIT count OFFSET offset、MySQL 的 LIMIT offset, count、OFFSET ... FETCH 和 TOP n
+            // 都映射到同一个结果窗口，三种写法只能用一种
+            let limits = [
+                query.limit.as_ref().map(|l| parse_limit_value(l, "LIMIT")).transpose()?,
+                query.fetch.as_ref().map(parse_fetch).transpose()?,
+                select.top.as_ref().map(parse_top).transpose()?,
+            ];
+            if limits.iter().flatten().count() > 1 {
+                return Err("Only one of LIMIT, FETCH and TOP may be used".into());
+            }
+            let limit = limits.into_iter().flatten().next();
+            let offset = query.offset.as_ref().map(|o| parse_limit_value(&o.value, "OFFSET")).transpose()?;
+
             Ok(SqlAst::Select {
                 table,
+                alias,
+                joins,
                 columns,
+                aliases,
                 where_clause,
+                group_by,
                 order_by,
+                limit,
+                offset,
+                distinct_on,
             })
         }
         _ => Err("Unsupported query type".into()),
     }
 }
 
+// 取出对象名中各段的原始值（去掉 "" 或 `` 引号），以便保留字可作为标识符
+fn object_name_to_string(name: &ObjectName) -> String {
+    name.0.iter()
+        .map(|ident| clean_identifier(&ident.value))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// 规范化表名：去掉首尾空白和分号，以及外层的 "..."、`...` 或 [...]
+fn clean_identifier(raw: &str) -> String {
+    let name = raw.trim().trim_end_matches(';').trim_end();
+    let unquoted = [('"', '"'), ('`', '`'), ('[', ']')].iter()
+        .find_map(|&(open, close)| name.strip_prefix(open)?.strip_suffix(close));
+    unquoted.unwrap_or(name).trim().to_string()
+}
+
+fn compound_identifier_to_string(idents: &[Ident]) -> String {
+    idents.iter()
+        .map(|ident| ident.value.as_str())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// 取出 FROM / JOIN 中的表名和别名
+fn parse_table_factor(relation: &TableFactor) -> Result<(String, Option<String>), String> {
+    match relation {
+        TableFactor::Table { name, alias, .. } => Ok((
+            object_name_to_string(name),
+            alias.as_ref().map(|a| a.name.value.clone()),
+        )),
+        _ => Err("Missing table name in FROM clause".into()),
+    }
+}
+
+fn parse_join(join: &Join) -> Result<DbJoin, String> {
+    let (table, alias) = parse_table_factor(&join.relation)?;
+    let (kind, constraint) = match &join.join_operator {
+        JoinOperator::Inner(constraint) => (JoinKind::Inner, constraint),
+        JoinOperator::LeftOuter(constraint) => (JoinKind::Left, constraint),
+        _ => return Err("Only INNER JOIN and LEFT JOIN are supported".into()),
+    };
+    let condition = match constraint {
+        JoinConstraint::On(expr) => {
+            let mut pairs = Vec::new();
+            collect_join_equalities(expr, &mut pairs)?;
+            JoinCondition::On(pairs)
+        }
+        JoinConstraint::Using(idents) => {
+            JoinCondition::Using(idents.iter().map(|i| i.value.clone()).collect())
+        }
+        _ => return Err("JOIN requires an ON or USING clause".into()),
+    };
+    Ok(DbJoin { kind, table, alias, condition })
+}
+
+// 展开 ON 条件中由 AND 连接的列等值比较
+fn collect_join_equalities(expr: &Expr, pairs: &mut Vec<(String, String)>) -> Result<(), String> {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            collect_join_equalities(left, pairs)?;
+            collect_join_equalities(right, pairs)
+        }
+        Expr::BinaryOp { left, op: BinaryOperator::Eq, right } => {
+            pairs.push((join_column(left)?, join_column(right)?));
+            Ok(())
+        }
+        Expr::Nested(inner) => collect_join_equalities(inner, pairs),
+        _ => Err(format!("Unsupported JOIN condition: {}", expr)),
+    }
+}
+
+fn join_column(expr: &Expr) -> Result<String, String> {
+    match expr {
+        Expr::Identifier(ident) => Ok(ident.value.clone()),
+        Expr::CompoundIdentifier(idents) => Ok(compound_identifier_to_string(idents)),
+        _ => Err(format!("JOIN conditions must compare columns, got: {}", expr)),
+    }
+}
+
+// 将投影表达式转换为列名或函数表达式文本
+fn parse_projection_expr(expr: &Expr) -> Result<String, String> {
+    match expr {
+        Expr::Identifier(ident) => Ok(ident.value.clone()),
+        Expr::CompoundIdentifier(idents) => Ok(compound_identifier_to_string(idents)),
+        Expr::Function(_) | Expr::Cast { .. } => Ok(expr.to_string()),
+        _ => Err("Unsupported column expression".into()),
+    }
+}
+
+fn is_random_function(name: &ObjectName) -> bool {
+    let name = object_name_to_string(name).to_uppercase();
+    name == "RANDOM" || name == "RAND"
+}
+
+// 解析 LIMIT / OFFSET / FETCH / TOP 中的行数（clause 为子句名，用于错误信息）；
+// 负数和非整数直接报错，超出 usize 的整数按 usize::MAX 处理，分页时不会溢出
+fn parse_limit_value(expr: &Expr, clause: &str) -> Result<usize, String> {
+    match expr {
+        Expr::Value(Value::Number(num, _)) if !num.is_empty() && num.bytes().all(|b| b.is_ascii_digit()) => {
+            Ok(num.parse::<usize>().unwrap_or(usize::MAX))
+        }
+        Expr::Value(Value::Number(num, _)) => Err(format!("Invalid {} value: {}", clause, num)),
+        Expr::Nested(inner) => parse_limit_value(inner, clause),
+        Expr::UnaryOp { op: UnaryOperator::Minus, expr: inner } if matches!(**inner, Expr::Value(Value::Number(..))) => {
+            Err(format!("{} must not be negative: {}", clause, expr))
+        }
+        _ => Err(format!("Unsupported {} expression: {}", clause, expr)),
+    }
+}
+
+// OFFSET n ROWS FETCH {FIRST|NEXT} [m] {ROW|ROWS} ONLY 等同于 LIMIT m；省略 m 时取一行
+fn parse_fetch(fetch: &Fetch) -> Result<usize, String> {
+    if fetch.with_ties || fetch.percent {
+        return Err("FETCH only supports ONLY with a row count".into());
+    }
+    fetch.quantity.as_ref().map_or(Ok(1), |q| parse_limit_value(q, "FETCH"))
+}
+
+// SQL Server 的 SELECT TOP n 等同于 LIMIT n
+fn parse_top(top: &Top) -> Result<usize, String> {
+    if top.with_ties || top.percent {
+        return Err("TOP only supports a row count".into());
+    }
+    top.quantity.as_ref().map(|q| parse_limit_value(q, "TOP")).ok_or("TOP requires a row count")?
+}
+
 // 计算表达式解析函数
 fn parse_calculation(input: &str) -> Result<SqlAst, String> {
-    // 支持带SELECT前缀或纯表达式
-    let expr = input.strip_prefix("SELECT ")
-        .unwrap_or(input)
-        .trim_end_matches(';')
-        .trim();
+    // 支持带SELECT前缀（不区分大小写）或纯表达式
+    let input = input.trim();
+    let expr = match input.get(..7) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("SELECT ") => &input[7..],
+        _ => input,
+    };
+    let expr = expr.trim_end_matches(';').trim();
 
     // 验证表达式有效性
     if expr.is_empty() {
@@ -305,7 +723,7 @@ fn parse_create_table(
     columns: Vec<ColumnDef>,
     constraints: Vec<TableConstraint>,
 ) -> Result<SqlAst, String> {
-    let table_name = name.to_string();
+    let table_name = object_name_to_string(&name);
     //println!("[DEBUG] 开始解析创建表: {}", table_name);
 
     // 1. 收集所有主键列名（从列级约束和表级约束）
@@ -337,6 +755,8 @@ fn parse_create_table(
 
     // 2. 处理列定义
     let mut parsed_columns = Vec::new();
+    let mut defaults = Vec::new();
+    let mut comments = Vec::new();
     for col in columns {
         let col_name = col.name.value;
         
@@ -353,6 +773,14 @@ fn parse_create_table(
                     not_null = true;
                     //println!("[DEBUG] 列 '{}' 显式设置了 NOT NULL", col_name);
                 }
+                // DEFAULT NULL 与不写默认值相同
+                ColumnOption::Default(expr) => {
+                    let value = parse_value_expr(expr.clone())?;
+                    if !value.is_empty() {
+                        defaults.push((col_name.clone(), value));
+                    }
+                }
+                ColumnOption::Comment(comment) => comments.push((col_name.clone(), comment.clone())),
                 _ => {}
             }
         }
@@ -375,13 +803,20 @@ fn parse_create_table(
     Ok(SqlAst::CreateTable {
         table_name,
         columns: parsed_columns,
+        defaults,
+        comments,
     })
 }
 
 
 
-fn parse_insert(table_name: ObjectName, columns: Vec<Ident>, source: Box<Query>) -> Result<SqlAst, String> {
-    let table = table_name.to_string();
+fn parse_insert(
+    table_name: ObjectName,
+    columns: Vec<Ident>,
+    source: Box<Query>,
+    returning: Option<Vec<SelectItem>>,
+) -> Result<SqlAst, String> {
+    let table = object_name_to_string(&table_name);
     
     // 处理列名 - 明确指定Option的类型
     let column_names: Option<Vec<String>> = if columns.is_empty() {
@@ -394,19 +829,9 @@ fn parse_insert(table_name: ObjectName, columns: Vec<Ident>, source: Box<Query>)
     let values = match *source.body {
         SetExpr::Values(values) => {
             values.rows.into_iter().map(|row| {
-                row.into_iter().map(|expr| {
-                    match expr {
-                        Expr::Value(value) => match value {
-                            Value::Number(num, _) => Ok(num),
-                            Value::SingleQuotedString(s) => Ok(s),
-                            Value::DoubleQuotedString(s) => Ok(s),
-                            Value::Null => Ok("NULL".to_string()),
-                            _ => Err(format!("Unsupported value type: {:?}", value)),
-                        },
-                        Expr::Identifier(ident) => Ok(ident.value),
-                        _ => Err(format!("Unsupported expression type in VALUES: {:?}", expr)),
-                    }
-                }).collect::<Result<Vec<String>, String>>()
+                row.into_iter()
+                    .map(parse_value_expr)
+                    .collect::<Result<Vec<String>, String>>()
             }).collect::<Result<Vec<Vec<String>>, String>>()?
         },
         _ => return Err("INSERT statement must use VALUES clause".into())
@@ -420,13 +845,80 @@ fn parse_insert(table_name: ObjectName, columns: Vec<Ident>, source: Box<Query>)
         }
     }
 
+    let returning = returning.unwrap_or_default().iter()
+        .map(parse_select_item)
+        .collect::<Result<Vec<_>, String>>()?;
+
     Ok(SqlAst::Insert {
         table,
         columns: column_names,
         values,
+        returning,
     })
 }
 
+// 查询列表（或 RETURNING）中的一项：(列表达式, 别名)
+fn parse_select_item(item: &SelectItem) -> Result<(String, Option<String>), String> {
+    match item {
+        SelectItem::UnnamedExpr(expr) => Ok((parse_projection_expr(expr)?, None)),
+        SelectItem::ExprWithAlias { expr, alias } => {
+            Ok((parse_projection_expr(expr)?, Some(alias.value.clone())))
+        }
+        SelectItem::Wildcard(_) => Ok(("*".to_string(), None)),
+        _ => Err("Unsupported column expression".to_string()),
+    }
+}
+
+// 将 VALUES 中的单个表达式转换为存储值
+fn parse_value_expr(expr: Expr) -> Result<String, String> {
+    match expr {
+        Expr::Value(value) => match value {
+            Value::Number(num, _) => Ok(num),
+            // 成对的单引号 '' 已由 sqlparser 还原，这里只需处理反斜杠转义
+            Value::SingleQuotedString(s) => Ok(unescape_string_literal(&s)),
+            Value::DoubleQuotedString(s) => Ok(unescape_string_literal(&s)),
+            Value::EscapedStringLiteral(s) => Ok(unescape_string_literal(&s)),
+            // NULL 关键字存储为空字符串，与字符串 'NULL' 区分
+            Value::Null => Ok(String::new()),
+            _ => Err(format!("Unsupported value type: {:?}", value)),
+        },
+        // 未加引号的 DEFAULT 表示使用列默认值
+        Expr::Identifier(ident) if ident.quote_style.is_none() && ident.value.eq_ignore_ascii_case("DEFAULT") => {
+            Ok(DEFAULT_VALUE.to_string())
+        }
+        Expr::Identifier(ident) => Ok(ident.value),
+        _ => Err(format!("Unsupported expression type in VALUES: {:?}", expr)),
+    }
+}
+
+// 还原字符串字面量中的反斜杠转义（\' \" \\ \n \t 等）
+fn unescape_string_literal(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('0') => result.push('\0'),
+            // LIKE 通配符的转义保持原样
+            Some(c @ ('%' | '_')) => {
+                result.push('\\');
+                result.push(c);
+            }
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
 
 
 
@@ -435,55 +927,55 @@ fn parse_update(
     assignments: Vec<Assignment>,
     selection: Option<Expr>,
 ) -> Result<SqlAst, String> {
-    let table_name = match table.relation {
-        TableFactor::Table { name, .. } => name.to_string(),
-        _ => return Err("Invalid table reference".into()),
-    };
+    let (table_name, alias) = parse_table_factor(&table.relation)
+        .map_err(|_| "Invalid table reference".to_string())?;
     
     let set = assignments
         .into_iter()
         .map(|assg| {
-            if assg.id.len() != 1 {
-                return Err(format!(
+            // SET 的目标列可以用别名或表名限定，例如 SET u.name = 'x'
+            let column_name = match &assg.id[..] {
+                [column] => column.value.clone(),
+                [qualifier, column] if alias.as_deref().unwrap_or(&table_name).eq_ignore_ascii_case(&qualifier.value) => {
+                    column.value.clone()
+                }
+                _ => return Err(format!(
                     "Expected single column name, found {}",
-                    assg.id.len()
-                ));
-            }
-            let column_name = assg.id[0].value.clone();
-            let value = assg.value.to_string();
+                    compound_identifier_to_string(&assg.id)
+                )),
+            };
+            // 字面量直接保存，其余（列名、函数等）作为表达式在更新时求值
+            let value = match assg.value {
+                Expr::Value(_) => parse_value_expr(assg.value)?,
+                expr => format!("{}{}", SET_EXPRESSION_PREFIX, expr),
+            };
             Ok((column_name, value))
         })
         .collect::<Result<Vec<(String, String)>, String>>()?;
     
-    let where_clause = selection.map(|expr| {
-        // 标准化条件表达式字符串
-        expr.to_string()
-            .replace('\'', "\"") // 正确写法：第一个参数是char，第二个是&str
-            .replace("IS NULL", "IS \"\"")  // 处理NULL情况
-            .replace("IS NOT NULL", "IS NOT \"\"")
-    });
+    // sqlparser 输出的字符串字面量中单引号已转义为 ''，条件解析时再还原
+    let where_clause = selection.map(|expr| expr.to_string());
     
     Ok(SqlAst::Update {
         table: table_name,
+        alias,
         set,
         where_clause,
     })
 }
 
 fn parse_delete(table_with_joins: TableWithJoins, selection: Option<Expr>) -> Result<SqlAst, String> {
-    let table_name = match table_with_joins.relation {
-        TableFactor::Table { name, .. } => {
-            match &name.0[..] {
-                [ident] => ident.value.clone(),
-                [schema, table] => format!("{}.{}", schema.value, table.value),
-                _ => return Err("Invalid table name format".into()),
-            }
+    let (table_name, alias) = match &table_with_joins.relation {
+        TableFactor::Table { name, .. } if !(1..=2).contains(&name.0.len()) => {
+            return Err("Invalid table name format".into());
         }
+        relation @ TableFactor::Table { .. } => parse_table_factor(relation)?,
         _ => return Err("DELETE only supports simple table targets".into()),
     };
 
     Ok(SqlAst::Delete {
         table: table_name,
+        alias,
         where_clause: selection.map(|e| e.to_string()),
     })
 }
@@ -491,7 +983,7 @@ fn parse_delete(table_with_joins: TableWithJoins, selection: Option<Expr>) -> Re
 fn parse_drop_table(names: Vec<ObjectName>, if_exists: bool) -> Result<SqlAst, String> {
     let tables = names
         .into_iter()
-        .map(|name| name.to_string())
+        .map(|name| object_name_to_string(&name))
         .collect();
     
     Ok(SqlAst::Drop { tables, if_exists })
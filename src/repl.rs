@@ -0,0 +1,352 @@
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::time::Duration;
+use crate::database::{parse_csv, Database, ProgressCallback};
+use crate::format::{format_table, NumberFormat};
+use crate::history::CommandHistory;
+use crate::parser::SqlAst;
+use crate::{execute_sql, execute_statements, split_statements_with_lines};
+
+// PEEK 显示的行数
+pub const PEEK_ROWS: usize = 5;
+
+// .load 读取数据时的结束标记（单独一行）
+pub const LOAD_TERMINATOR: &str = "\\.";
+
+// .progress on 时每处理多少行输出一次进度
+pub const PROGRESS_INTERVAL: usize = 10_000;
+
+// REPL 运行时设置（通过 . 开头的命令修改）
+#[derive(Debug, Clone)]
+pub struct ReplSettings {
+    pub autosave: bool,  // 每次执行后是否立即写盘
+    pub force: bool,     // 不带 WHERE 的 DELETE/UPDATE 是否跳过确认
+    pub timing: bool,    // 是否在每次执行后显示耗时
+    pub thousands_separator: bool,  // 显示查询结果时 INT 列按千位分组（不影响存储和比较）
+    pub empty_result_headers: bool, // 查询无结果时是否仍输出表头
+    pub number_format: NumberFormat, // 计算结果（SELECT 1 + 2 之类）的显示方式
+}
+
+impl Default for ReplSettings {
+    fn default() -> Self {
+        Self {
+            autosave: true,
+            force: false,
+            timing: false,
+            thousands_separator: false,
+            empty_result_headers: false,
+            number_format: NumberFormat::default(),
+        }
+    }
+}
+
+// 不带 WHERE 的 DELETE/UPDATE 会影响整张表，返回该表名以便确认
+pub fn affects_all_rows(ast: &SqlAst) -> Option<&str> {
+    match ast {
+        SqlAst::Delete { table, where_clause: None, .. } => Some(table),
+        SqlAst::Update { table, where_clause: None, .. } => Some(table),
+        _ => None,
+    }
+}
+
+// 将耗时格式化为 (0.42 ms)
+pub fn format_elapsed(elapsed: Duration) -> String {
+    format!("({:.2} ms)", elapsed.as_secs_f64() * 1000.0)
+}
+
+// 执行一次输入；关闭自动保存时修改只保留在内存中
+pub fn run_input(
+    input: &str,
+    db: &mut Database,
+    settings: &ReplSettings,
+) -> bool {
+    if settings.autosave {
+        execute_sql(input, db, settings)
+    } else {
+        execute_statements(input, db, settings)
+    }
+}
+
+// 退出前保存数据库（只读模式除外）和命令历史，正常退出与 Ctrl-C 共用
+pub fn save_on_shutdown(db: &Database, history: &CommandHistory, history_path: &Path) -> Result<(), String> {
+    if !db.is_read_only() {
+        db.save().map_err(|e| format!("Failed to save database: {}", e))?;
+    }
+    history.save_to(history_path)
+        .map_err(|e| format!("Failed to save history: {}", e))
+}
+
+// 执行 SQL 文件中的所有语句，逐条报告结果，返回 (成功数, 失败数)
+pub fn source_file(
+    path: &Path,
+    db: &mut Database,
+    settings: &ReplSettings,
+) -> Result<(usize, usize), String> {
+    let script = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let mut succeeded = 0;
+    let mut failed = 0;
+    for (line, stmt) in split_statements_with_lines(&script) {
+        if run_input(&stmt, db, settings) {
+            succeeded += 1;
+            println!("[line {}] OK", line);
+        } else {
+            failed += 1;
+            eprintln!("[line {}] FAILED: {}", line, stmt);
+        }
+    }
+
+    Ok((succeeded, failed))
+}
+
+// 把 .load 的一行逗号分隔值解析为一行数据（支持 CSV 引号），字段数必须等于列数
+pub fn parse_load_line(line: &str, column_count: usize) -> Result<Vec<String>, String> {
+    let mut records = parse_csv(line)?;
+    let row = match records.len() {
+        1 => records.remove(0),
+        _ => return Err("Expected exactly one row per line".into()),
+    };
+    if row.len() != column_count {
+        return Err(format!("Expected {} values, got {}", column_count, row.len()));
+    }
+    Ok(row)
+}
+
+// .load table：逐行读取逗号分隔值并插入，直到结束标记或输入结束；
+// 出错的行报告行号后跳过，返回 (成功数, 失败数)
+pub fn load_rows<R: BufRead>(reader: R, db: &mut Database, table_name: &str) -> Result<(usize, usize), String> {
+    let (table_name, column_count) = db.table(table_name)
+        .map(|t| (t.name.clone(), t.columns.len()))
+        .ok_or(format!("Table '{}' not found", table_name))?;
+
+    // 整个 .load 只保存一个快照，UNDO 一次撤销全部导入的行
+    db.take_snapshot();
+    let mut succeeded = 0;
+    let mut failed = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read input: {}", e))?;
+        let line = line.trim_end_matches('\r');
+        if line.trim() == LOAD_TERMINATOR {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result = parse_load_line(line, column_count)
+            .and_then(|row| db.insert_rows(&table_name, None, vec![row.iter().map(String::as_str).collect()]).map_err(String::from));
+        match result {
+            Ok(_) => succeeded += 1,
+            Err(e) => {
+                failed += 1;
+                eprintln!("[line {}] {}", i + 1, e);
+            }
+        }
+    }
+    Ok((succeeded, failed))
+}
+
+// PEEK table：以表格形式显示表的前几行
+pub fn peek(db: &Database, table_name: &str) -> Result<String, String> {
+    let rows = db.sample(table_name, PEEK_ROWS)?;
+    let headers = db.columns(table_name)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    Ok(format_table(headers, rows))
+}
+
+// 正在输入的（可能跨多行的）命令；单独一行 \c 或连续两个空行会丢弃已输入的内容
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+    text: String,
+    last_line_blank: bool,
+}
+
+impl InputBuffer {
+    // 追加一行输入；返回 true 表示这一行取消了输入，缓冲区已清空
+    pub fn push_line(&mut self, line: &str) -> bool {
+        let content = line.trim();
+        let blank = content.is_empty();
+        if content == "\\c" || (blank && self.last_line_blank) {
+            self.clear();
+            return true;
+        }
+        self.last_line_blank = blank;
+        self.text.push_str(line);
+        false
+    }
+
+    // 用历史命令替换当前内容
+    pub fn replace(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.last_line_blank = false;
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.last_line_blank = false;
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+// 是否为 . 开头的 REPL 命令（单行，不需要分号结尾）
+pub fn is_dot_command(input: &str) -> bool {
+    input.trim_start().starts_with('.')
+}
+
+// 处理 . 开头的 REPL 命令
+pub fn handle_dot_command(input: &str, db: &mut Database, settings: &mut ReplSettings) {
+    let cmd = input.trim().trim_end_matches(';').trim();
+    let parts: Vec<&str> = cmd.split_whitespace().collect();
+
+    match parts.as_slice() {
+        [".autosave", arg] => match parse_switch(arg) {
+            Some(on) => {
+                settings.autosave = on;
+                println!("Autosave {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .autosave on|off"),
+        },
+        [".autosave"] => {
+            println!("Autosave is {}", if settings.autosave { "on" } else { "off" });
+        }
+        [".force", arg] => match parse_switch(arg) {
+            Some(on) => {
+                settings.force = on;
+                println!("Force mode {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .force on|off"),
+        },
+        [".headers", arg] => match parse_switch(arg) {
+            Some(on) => {
+                settings.empty_result_headers = on;
+                println!("Empty result headers {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .headers on|off"),
+        },
+        [".readonly", arg] => match parse_switch(arg) {
+            Some(on) => {
+                db.set_read_only(on);
+                println!("Read-only mode {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .readonly on|off"),
+        },
+        [".timing", arg] => match parse_switch(arg) {
+            Some(on) => {
+                settings.timing = on;
+                println!("Timing {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .timing on|off"),
+        },
+        [".compact", arg] => match parse_switch(arg) {
+            Some(on) => {
+                db.set_compact_json(on);
+                println!("Compact JSON {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .compact on|off"),
+        },
+        [".strict", arg] => match parse_switch(arg) {
+            Some(on) => {
+                db.set_strict_mode(on);
+                println!("Strict mode {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .strict on|off"),
+        },
+        [".trim", arg] => match parse_switch(arg) {
+            Some(on) => {
+                db.set_trim_varchar(on);
+                println!("Trim VARCHAR values {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .trim on|off"),
+        },
+        [".thousands", arg] => match parse_switch(arg) {
+            Some(on) => {
+                settings.thousands_separator = on;
+                println!("Thousands separator {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .thousands on|off"),
+        },
+        [".progress", arg] => match parse_switch(arg) {
+            Some(on) => {
+                let callback: ProgressCallback = Box::new(|n| eprintln!("... {} rows processed", n));
+                db.set_progress(on.then_some((PROGRESS_INTERVAL, callback)));
+                println!("Progress {}", if on { "on" } else { "off" });
+            }
+            None => eprintln!("Error: Usage: .progress on|off"),
+        },
+        [".numformat"] => println!("Number format: {}", describe_number_format(&settings.number_format)),
+        [".numformat", args @ ..] => match parse_number_format(args) {
+            Some(fmt) => {
+                settings.number_format = fmt;
+                println!("Number format: {}", describe_number_format(&fmt));
+            }
+            None => eprintln!("Error: Usage: .numformat plain|sci [decimals]"),
+        },
+        [".load", table] => {
+            println!("Enter comma-separated values, end with a line containing only {}", LOAD_TERMINATOR);
+            match load_rows(io::stdin().lock(), db, table) {
+                Ok((ok, failed)) => {
+                    println!("Loaded {} row(s), {} failed", ok, failed);
+                    let saved = if ok > 0 && settings.autosave { db.save() } else { Ok(()) };
+                    if let Err(e) = saved {
+                        eprintln!("Failed to save database: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        [".schema"] => {
+            for table in db.tables_iter() {
+                println!("{}", db.create_statement(&table.name).unwrap_or_default());
+            }
+        }
+        [".schema", table] => match db.create_statement(table) {
+            Some(ddl) => println!("{}", ddl),
+            None => eprintln!("Error: Table '{}' not found", table),
+        },
+        [".save"] => match db.save() {
+            Ok(_) => println!("Database saved to {}", db.path().display()),
+            Err(e) => eprintln!("Failed to save database: {}", e),
+        },
+        _ => eprintln!("Error: Unknown command '{}'", cmd),
+    }
+}
+
+// 解析 .numformat 的参数：plain|sci [小数位数]
+fn parse_number_format(args: &[&str]) -> Option<NumberFormat> {
+    let (mode, decimals) = match args {
+        [mode] => (mode, None),
+        [mode, decimals] => (mode, Some(decimals.parse::<usize>().ok()?)),
+        _ => return None,
+    };
+    let scientific = match mode.to_lowercase().as_str() {
+        "plain" => false,
+        "sci" => true,
+        _ => return None,
+    };
+    Some(NumberFormat { scientific, decimals })
+}
+
+// .numformat 的当前设置，例如 sci, 3 decimals
+fn describe_number_format(fmt: &NumberFormat) -> String {
+    let mode = if fmt.scientific { "sci" } else { "plain" };
+    match fmt.decimals {
+        Some(d) => format!("{}, {} decimals", mode, d),
+        None => mode.to_string(),
+    }
+}
+
+// 解析 on/off 开关
+fn parse_switch(arg: &str) -> Option<bool> {
+    match arg.to_lowercase().as_str() {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,58 @@
+use rustique_db::cache::ParseCache;
+use rustique_db::database::{DataType, Database};
+use rustique_db::execute_statements;
+use rustique_db::repl::ReplSettings;
+
+fn setup() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![vec!["1", "Alice"]]).unwrap();
+    db
+}
+
+#[test]
+fn repeated_query_hits_cache() {
+    let mut db = setup();
+
+    assert!(execute_statements("SELECT name FROM users;", &mut db, &ReplSettings::default()));
+    assert_eq!((db.parse_cache().hits(), db.parse_cache().misses()), (0, 1));
+
+    // 空白和结尾分号的差异不影响命中
+    assert!(execute_statements("SELECT  name\nFROM users", &mut db, &ReplSettings::default()));
+    assert_eq!((db.parse_cache().hits(), db.parse_cache().misses()), (1, 1));
+    assert_eq!(db.parse_cache().len(), 1);
+}
+
+#[test]
+fn ddl_invalidates_cache() {
+    let mut db = setup();
+
+    execute_statements("SELECT name FROM users;", &mut db, &ReplSettings::default());
+    assert_eq!(db.parse_cache().len(), 1);
+
+    db.create_table("tags", vec![("name", DataType::Varchar(10), false, false)]).unwrap();
+    assert!(db.parse_cache().is_empty());
+
+    execute_statements("SELECT name FROM users;", &mut db, &ReplSettings::default());
+    assert_eq!(db.parse_cache().hits(), 0);
+    execute_statements("DROP TABLE tags;", &mut db, &ReplSettings::default());
+    assert!(db.parse_cache().is_empty());
+}
+
+#[test]
+fn least_recently_used_entry_is_evicted() {
+    let mut cache = ParseCache::new(2);
+    cache.parse("SELECT a FROM t").unwrap();
+    cache.parse("SELECT b FROM t").unwrap();
+    cache.parse("SELECT a FROM t").unwrap(); // a 变为最近使用
+    cache.parse("SELECT c FROM t").unwrap(); // 淘汰 b
+    assert_eq!(cache.len(), 2);
+
+    cache.parse("SELECT a FROM t").unwrap();
+    assert_eq!(cache.hits(), 2);
+    cache.parse("SELECT b FROM t").unwrap();
+    assert_eq!(cache.hits(), 2);
+}
@@ -0,0 +1,123 @@
+use rustique_db::database::Database;
+use rustique_db::format::{format_number, NumberFormat};
+use rustique_db::parser::{parse_sql, parse_sql_with, SqlAst, NUMERIC_OVERFLOW};
+use rustique_db::repl::{handle_dot_command, ReplSettings};
+use rustique_db::{run_statement, QueryResult};
+
+fn calculate(sql: &str) -> Result<f64, String> {
+    match parse_sql(sql)? {
+        SqlAst::Calculate { result, .. } => Ok(result),
+        other => panic!("expected calculation, got {:?}", other),
+    }
+}
+
+#[test]
+fn overflowing_expression_is_an_error() {
+    let big = "9".repeat(200);
+    let err = calculate(&format!("SELECT {} * {}", big, big)).unwrap_err();
+    assert_eq!(err, NUMERIC_OVERFLOW);
+    assert_eq!(err, "numeric overflow");
+}
+
+#[test]
+fn literal_beyond_f64_range_is_an_error() {
+    let huge = "9".repeat(400);
+    assert_eq!(calculate(&format!("SELECT {} + 1", huge)).unwrap_err(), NUMERIC_OVERFLOW);
+}
+
+#[test]
+fn zero_divided_by_zero_is_division_error_not_nan() {
+    assert_eq!(calculate("SELECT 0 / 0").unwrap_err(), "Division by zero");
+}
+
+#[test]
+fn normal_expressions_still_evaluate() {
+    assert_eq!(calculate("SELECT (1 + 2) * 3").unwrap(), 9.0);
+}
+
+#[test]
+fn float_and_integer_division() {
+    assert_eq!(calculate("SELECT 7 / 2").unwrap(), 3.5);
+    assert_eq!(calculate("SELECT 7 // 2").unwrap(), 3.0);
+    assert_eq!(calculate("SELECT 7 DIV 2").unwrap(), 3.0);
+    assert_eq!(calculate("SELECT 7 div 2").unwrap(), 3.0);
+    // 向零截断
+    assert_eq!(calculate("SELECT (0 - 7) DIV 2").unwrap(), -3.0);
+    // 与 * / 同一优先级，从左到右结合
+    assert_eq!(calculate("SELECT 1 + 9 // 2 * 2").unwrap(), 9.0);
+}
+
+#[test]
+fn integer_division_by_zero_is_an_error() {
+    assert_eq!(calculate("SELECT 7 // 0").unwrap_err(), "Division by zero");
+    assert_eq!(calculate("SELECT 7 DIV 0").unwrap_err(), "Division by zero");
+}
+
+#[test]
+fn strict_mode_reports_misspelled_select() {
+    let err = parse_sql_with("SELCT * FROM users", true).unwrap_err();
+    assert_eq!(err, "Syntax error: unknown statement 'SELCT'");
+
+    // 非严格模式仍按计算表达式处理（得到计算错误）
+    assert!(parse_sql("SELCT * FROM users").is_err());
+
+    // 严格模式下纯计算照常工作
+    match parse_sql_with("SELECT 1 + 2", true).unwrap() {
+        SqlAst::Calculate { result, .. } => assert_eq!(result, 3.0),
+        other => panic!("expected calculation, got {:?}", other),
+    }
+}
+
+#[test]
+fn strict_mode_rejects_unsupported_statement() {
+    let mut db = Database::new();
+    db.set_strict_mode(true);
+    let err = db.parse_cached("ALTER TABLE users ADD COLUMN age INT").unwrap_err();
+    assert!(err.contains("ALTER"), "{}", err);
+    assert!(db.strict_mode());
+}
+
+#[test]
+fn constant_select_and_bare_expression_are_consistent() {
+    let label = |sql: &str| match parse_sql(sql).unwrap() {
+        SqlAst::Calculate { expression, result } => (expression, result),
+        other => panic!("expected calculation, got {:?}", other),
+    };
+    assert_eq!(label("SELECT 1"), ("1".to_string(), 1.0));
+    assert_eq!(label("select 1+1;"), ("1+1".to_string(), 2.0));
+    assert_eq!(label("1+1"), ("1+1".to_string(), 2.0));
+    assert_eq!(label("SELECT 1 + 1"), label("1 + 1"));
+}
+
+#[test]
+fn number_format_switches_to_scientific_only_for_extreme_magnitudes() {
+    let sci = NumberFormat { scientific: true, decimals: None };
+    assert_eq!(format_number(1_000_000_000_000.0, &sci), "1e12");
+    assert_eq!(format_number(-2.5e15, &sci), "-2.5e15");
+    assert_eq!(format_number(0.00000012, &sci), "1.2e-7");
+    assert_eq!(format_number(1234.5, &sci), "1234.5");
+    assert_eq!(format_number(0.0, &sci), "0");
+
+    let sci3 = NumberFormat { scientific: true, decimals: Some(3) };
+    assert_eq!(format_number(123_456_789_000_000.0, &sci3), "1.235e14");
+    assert_eq!(format_number(2.0 / 3.0, &sci3), "0.667");
+
+    let plain = NumberFormat::default();
+    assert_eq!(format_number(1_000_000_000_000.0, &plain), "1000000000000");
+    assert_eq!(format_number(0.5, &NumberFormat { scientific: false, decimals: Some(2) }), "0.50");
+}
+
+#[test]
+fn number_format_only_affects_display() {
+    let mut db = Database::new();
+    let mut settings = ReplSettings::default();
+    handle_dot_command(".numformat sci 2", &mut db, &mut settings);
+    assert_eq!(settings.number_format, NumberFormat { scientific: true, decimals: Some(2) });
+
+    // run_statement 返回原值，格式化只在打印时进行
+    let result = run_statement("SELECT 1000000000000 * 10", &mut db).unwrap();
+    assert_eq!(result, QueryResult::Rows {
+        headers: vec!["1000000000000 * 10".to_string()],
+        rows: vec![vec!["10000000000000".to_string()]],
+    });
+}
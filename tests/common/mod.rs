@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use rustique_db::database::Database;
+use rustique_db::parser::{parse_sql, SqlAst};
+
+// 解析并执行 SELECT，返回结果行
+pub fn run_select(db: &Database, sql: &str) -> Result<Vec<Vec<String>>, String> {
+    match parse_sql(sql)? {
+        SqlAst::Select { table, alias, joins, columns, where_clause, group_by, order_by, limit, offset, .. } => {
+            let cols: Vec<&str> = columns.iter().map(|s| s.as_str()).collect();
+            let keys: Vec<&str> = group_by.iter().map(|s| s.as_str()).collect();
+            let order: Vec<(&str, bool)> = order_by.iter().map(|(c, d)| (c.as_str(), *d)).collect();
+            if joins.is_empty() && alias.is_none() {
+                db.select_grouped(&table, cols, where_clause.as_deref(), keys, Some(order), limit, offset)
+            } else {
+                let joined = db.joined_table(&table, alias.as_deref(), &joins)?;
+                db.select_grouped_from(&joined, cols, where_clause.as_deref(), keys, Some(order), limit, offset)
+            }
+        }
+        other => panic!("expected SELECT, got {:?}", other),
+    }
+}
+
+// 解析并执行一条修改语句（不打印、不落盘），返回受影响行数
+pub fn run(db: &mut Database, sql: &str) -> Result<usize, String> {
+    match parse_sql(sql)? {
+        SqlAst::CreateTable { table_name, columns, defaults, comments } => {
+            let col_defs: Vec<(&str, _, bool, bool)> = columns.iter()
+                .map(|(name, dt, pk, nn)| (name.as_str(), dt.clone(), *pk, *nn))
+                .collect();
+            db.create_table(&table_name, col_defs)?;
+            for (col, value) in &defaults {
+                db.set_column_default(&table_name, col, Some(value))?;
+            }
+            for (col, comment) in &comments {
+                db.set_column_comment(&table_name, col, Some(comment))?;
+            }
+            Ok(0)
+        }
+        SqlAst::Insert { table, columns, values, .. } => {
+            let values_ref: Vec<Vec<&str>> = values.iter()
+                .map(|row| row.iter().map(|s| s.as_str()).collect())
+                .collect();
+            db.insert(&table, columns, values_ref).map_err(String::from)
+        }
+        SqlAst::Update { table, alias, set, where_clause } => {
+            db.update_with_alias(&table, alias.as_deref(), set, where_clause.as_deref())
+        }
+        SqlAst::Delete { table, alias, where_clause } => {
+            db.delete_with_alias(&table, alias.as_deref(), where_clause.as_deref()).map_err(String::from)
+        }
+        SqlAst::Drop { tables, if_exists } => db.drop_tables(&tables, if_exists),
+        other => panic!("unexpected statement: {:?}", other),
+    }
+}
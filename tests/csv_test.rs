@@ -0,0 +1,50 @@
+use rustique_db::database::{parse_csv, DataType, Database};
+
+fn setup_notes() -> Database {
+    let mut db = Database::new();
+    db.create_table("notes", vec![
+        ("id", DataType::Int(10), true, true),
+        ("body", DataType::Varchar(100), false, false),
+    ]).unwrap();
+    db
+}
+
+fn bodies(db: &Database) -> Vec<String> {
+    db.scan("notes").unwrap().map(|row| row[1].to_string()).collect()
+}
+
+#[test]
+fn quoted_fields_keep_commas_newlines_and_quotes() {
+    let mut db = setup_notes();
+    let csv = "id,body\n1,\"a, b\"\n2,\"line one\nline two\"\r\n3,\"say \"\"hi\"\"\"\n";
+    assert_eq!(db.import_csv("notes", csv).unwrap(), 3);
+    assert_eq!(bodies(&db), vec!["a, b", "line one\nline two", "say \"hi\""]);
+}
+
+#[test]
+fn header_selects_columns_and_empty_fields_are_null() {
+    let mut db = setup_notes();
+    assert_eq!(db.import_csv("notes", "body,id\nhello,1\n,2").unwrap(), 2);
+    let rows: Vec<Vec<String>> = db.scan("notes").unwrap()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect();
+    assert_eq!(rows, vec![vec!["1", "hello"], vec!["2", ""]]);
+    assert!(db.scan("notes").unwrap().nth(1).unwrap()[1].is_null());
+}
+
+#[test]
+fn malformed_quoting_is_an_error() {
+    assert_eq!(parse_csv("a,\"open\nb").unwrap_err(), "Unterminated quoted field starting on line 1");
+    assert!(parse_csv("\"x\"y,z").unwrap_err().contains("after quoted field"));
+
+    let mut db = setup_notes();
+    assert!(db.import_csv("notes", "id,body\n1,\"oops").is_err());
+    assert_eq!(db.row_count("notes"), Some(0));
+}
+
+#[test]
+fn parse_csv_handles_empty_input_and_trailing_newline() {
+    assert!(parse_csv("").unwrap().is_empty());
+    assert_eq!(parse_csv("a,b\n").unwrap(), vec![vec!["a", "b"]]);
+    assert_eq!(parse_csv("a,\"\"\n").unwrap(), vec![vec!["a", ""]]);
+}
@@ -0,0 +1,517 @@
+use rustique_db::database::{DataType, Database, Value};
+use rustique_db::error::DbError;
+
+fn setup_users() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![vec!["1", "Alice"], vec!["2", "Bob"]]).unwrap();
+    db
+}
+
+#[test]
+fn table_exists_is_case_insensitive() {
+    let db = setup_users();
+    assert!(db.table_exists("users"));
+    assert!(db.table_exists("USERS"));
+    assert!(!db.table_exists("orders"));
+}
+
+#[test]
+fn row_count_for_existing_and_missing_tables() {
+    let db = setup_users();
+    assert_eq!(db.row_count("users"), Some(2));
+    assert_eq!(db.row_count("Users"), Some(2));
+    assert_eq!(db.row_count("orders"), None);
+}
+
+#[test]
+fn table_accessor_and_iteration() {
+    let mut db = setup_users();
+    db.create_table("orders", vec![("id", DataType::Int(10), true, true)]).unwrap();
+
+    let users = db.table("USERS").unwrap();
+    assert_eq!(users.name, "users");
+    assert_eq!(users.data.len(), 2);
+    assert!(db.table("missing").is_none());
+
+    let names: Vec<&str> = db.tables_iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["users", "orders"]);
+}
+
+#[test]
+fn table_builder_matches_tuple_form() {
+    use rustique_db::database::TableBuilder;
+
+    let built = TableBuilder::new()
+        .column("id", DataType::Int(10)).primary_key().not_null()
+        .column("name", DataType::Varchar(50)).not_null()
+        .column("age", DataType::Int(10))
+        .build();
+
+    assert_eq!(built, vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, true),
+        ("age", DataType::Int(10), false, false),
+    ]);
+
+    let mut db = Database::new();
+    db.create_table("people", built).unwrap();
+    let table = db.table("people").unwrap();
+    assert!(table.columns[0].is_primary);
+    assert!(table.columns[1].not_null);
+    assert!(!table.columns[2].not_null);
+}
+
+#[test]
+fn second_lock_on_same_path_fails_until_released() {
+    use rustique_db::database::DbLock;
+
+    let dir = std::env::temp_dir().join(format!("rustique_lock_{}", std::process::id()));
+    let db_path = dir.join("db.json");
+
+    let first = DbLock::acquire(&db_path).unwrap();
+    assert!(first.path().exists());
+
+    let err = DbLock::acquire(&db_path).unwrap_err();
+    assert!(matches!(err, DbError::Locked(_)), "unexpected error: {}", err);
+
+    drop(first);
+    let again = DbLock::acquire(&db_path).unwrap();
+    drop(again);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn analyze_counts_nulls_and_distinct_values() {
+    let mut db = Database::new();
+    db.create_table("people", vec![
+        ("id", DataType::Int(10), true, true),
+        ("city", DataType::Varchar(20), false, false),
+        ("age", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("people", None, vec![
+        vec!["1", "Paris", "30"],
+        vec!["2", "", "30"],
+        vec!["3", "Paris", ""],
+        vec!["4", "Rome", ""],
+        vec!["5", "", "41"],
+    ]).unwrap();
+
+    let stats = db.analyze("PEOPLE").unwrap();
+    let summary: Vec<(&str, usize, usize, usize)> = stats.iter()
+        .map(|s| (s.column.as_str(), s.total, s.nulls, s.distinct))
+        .collect();
+    assert_eq!(summary, vec![
+        ("id", 5, 0, 5),
+        ("city", 5, 2, 2),
+        ("age", 5, 2, 2),
+    ]);
+
+    assert!(db.analyze("missing").is_err());
+}
+
+#[test]
+fn clear_all_then_save_writes_empty_database() {
+    use rustique_db::history::CommandHistory;
+
+    let dir = std::env::temp_dir().join(format!("rustique_clear_all_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.set_path(&db_path);
+    db.save().unwrap();
+
+    db.clear_all().unwrap();
+    assert_eq!(db.tables_iter().count(), 0);
+    assert!(!db.table_exists("users"));
+    db.save().unwrap();
+
+    let mut history = CommandHistory::new(10);
+    let reloaded = Database::load_with_history_from(&db_path, &dir.join("history.json"), &mut history).unwrap();
+    assert_eq!(reloaded.tables_iter().count(), 0);
+
+    drop(reloaded);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn drop_all_deletes_file_and_clears_tables() {
+    let dir = std::env::temp_dir().join(format!("rustique_drop_all_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.set_path(&db_path);
+    db.save().unwrap();
+    assert!(db_path.exists());
+
+    db.drop_all().unwrap();
+    assert!(!db_path.exists());
+    assert_eq!(db.tables_iter().count(), 0);
+    assert!(db.undo().is_err());
+
+    // 退出或自动保存时不会重新创建文件，直到再次建表
+    db.save().unwrap();
+    assert!(!db_path.exists());
+    db.create_table("t", vec![("id", DataType::Int(10), true, true)]).unwrap();
+    db.save().unwrap();
+    assert!(db_path.exists());
+
+    // 文件已经不存在时再次删除也不报错
+    db.drop_all().unwrap();
+    db.drop_all().unwrap();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn columns_returns_declared_schema() {
+    use rustique_db::database::Column;
+
+    let db = setup_users();
+    let columns = db.columns("users").unwrap();
+    assert_eq!(columns, vec![
+        Column { name: "id".into(), data_type: DataType::Int(10), is_primary: true, not_null: true, default: None, comment: None },
+        Column { name: "name".into(), data_type: DataType::Varchar(50), is_primary: false, not_null: false, default: None, comment: None },
+    ]);
+    assert!(db.columns("missing").is_none());
+}
+
+#[test]
+fn scan_streams_rows_without_collecting() {
+    let db = setup_users();
+    assert_eq!(db.scan("users").unwrap().count(), 2);
+
+    let bobs = db.scan("USERS").unwrap().filter(|row| row[1] == "Bob").count();
+    assert_eq!(bobs, 1);
+
+    let first: &[Value] = db.scan("users").unwrap().next().unwrap();
+    assert_eq!(first, [Value::Int(1), Value::Text("Alice".into())]);
+    assert!(db.scan("missing").is_none());
+}
+
+#[test]
+fn read_only_rejects_mutations_but_allows_select() {
+    let dir = std::env::temp_dir().join(format!("rustique_readonly_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.set_path(&db_path);
+    db.save().unwrap();
+    drop(db);
+
+    let mut db = Database::load_readonly_from(&db_path).unwrap();
+    assert!(db.is_read_only());
+
+    let read_only = DbError::ReadOnly.to_string();
+    assert!(matches!(db.insert("users", None, vec![vec!["3", "Carol"]]), Err(DbError::ReadOnly)));
+    assert_eq!(db.update("users", vec![("name".into(), "'X'".into())], None).unwrap_err(), read_only);
+    assert!(matches!(db.delete("users", None), Err(DbError::ReadOnly)));
+    assert_eq!(db.drop_tables(&["users".to_string()], false).unwrap_err(), read_only);
+    assert_eq!(db.create_table("orders", vec![("id", DataType::Int(10), true, true)]).unwrap_err(), read_only);
+    assert!(matches!(db.clear_all(), Err(DbError::ReadOnly)));
+    assert_eq!(db.save().unwrap_err(), read_only);
+
+    let rows = db.select("users", vec!["name"], Some("id = 2"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["Bob".to_string()]]);
+    assert_eq!(db.row_count("users"), Some(2));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn optimize_table_reports_rows_and_index_entries() {
+    use rustique_db::database::OptimizeStats;
+
+    let mut db = setup_users();
+    db.insert("users", None, vec![vec!["3", "Carol"], vec!["4", "Dave"]]).unwrap();
+    db.delete("users", Some("id > 2")).unwrap();
+
+    let stats = db.optimize_table("USERS").unwrap();
+    assert_eq!(stats, OptimizeStats { table: "users".into(), rows: 2, index_entries: 2 });
+
+    db.create_table("log", vec![("msg", DataType::Varchar(20), false, false)]).unwrap();
+    db.insert("log", None, vec![vec!["a"], vec!["a"]]).unwrap();
+    let stats = db.optimize_table("log").unwrap();
+    assert_eq!((stats.rows, stats.index_entries), (2, 0));
+
+    assert!(db.optimize_table("missing").is_err());
+}
+
+#[test]
+fn compact_json_has_no_newlines_and_round_trips() {
+    use rustique_db::history::CommandHistory;
+
+    let dir = std::env::temp_dir().join(format!("rustique_compact_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.set_path(&db_path);
+    db.save().unwrap();
+    let pretty = std::fs::read_to_string(&db_path).unwrap();
+    assert!(pretty.contains('\n'));
+
+    db.set_compact_json(true);
+    db.save().unwrap();
+    let compact = std::fs::read_to_string(&db_path).unwrap();
+    assert!(!compact.contains('\n'));
+    assert!(compact.len() < pretty.len());
+    drop(db);
+
+    let mut history = CommandHistory::new(10);
+    let reloaded = Database::load_with_history_from(&db_path, &dir.join("history.json"), &mut history).unwrap();
+    let rows = reloaded.select("users", vec!["id", "name"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["1".to_string(), "Alice".to_string()],
+        vec!["2".to_string(), "Bob".to_string()],
+    ]);
+
+    drop(reloaded);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn count_where_with_compound_condition() {
+    let mut db = setup_users();
+    db.insert("users", None, vec![vec!["3", "Carol"], vec!["4", "Bob"], vec!["5", "Eve"]]).unwrap();
+
+    assert_eq!(db.count_where("users", "id > 1 AND id < 5").unwrap(), 3);
+    assert_eq!(db.count_where("users", "name = 'Bob' AND id > 2").unwrap(), 1);
+    assert_eq!(db.count_where("users", "id > 100").unwrap(), 0);
+
+    assert!(matches!(db.count_where("missing", "id = 1"), Err(DbError::TableNotFound(_))));
+    assert!(matches!(db.count_where("users", "nope = 1"), Err(DbError::Parse(_))));
+}
+
+#[test]
+fn int_cells_compare_numerically() {
+    let mut db = Database::new();
+    db.create_table("t", vec![
+        ("n", DataType::Int(10), false, false),
+        ("s", DataType::Varchar(10), false, false),
+    ]).unwrap();
+    db.insert("t", None, vec![vec!["9", "9"], vec!["10", "10"], vec!["", ""]]).unwrap();
+
+    // 整数按数值比较，文本按字符串比较，NULL 不满足比较
+    assert_eq!(db.count_where("t", "n > 9").unwrap(), 1);
+    assert_eq!(db.count_where("t", "s > '9'").unwrap(), 0);
+    assert_eq!(db.count_where("t", "n < 100").unwrap(), 2);
+
+    let sorted = db.select("t", vec!["n"], None, Some(vec![("n", false)]), None, None).unwrap();
+    assert_eq!(sorted, vec![vec![""], vec!["9"], vec!["10"]]);
+}
+
+#[test]
+fn typed_values_round_trip_through_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("rustique_values_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.insert("users", None, vec![vec!["3", ""]]).unwrap();
+    db.set_path(&db_path);
+    db.save().unwrap();
+
+    let loaded = Database::load_readonly_from(&db_path).unwrap();
+    let rows: Vec<&[Value]> = loaded.scan("users").unwrap().collect();
+    assert_eq!(rows[0], [Value::Int(1), Value::Text("Alice".into())]);
+    assert_eq!(rows[2], [Value::Int(3), Value::Null]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn schema_json_describes_tables_without_data() {
+    let mut db = setup_users();
+    db.set_column_default("users", "name", Some("anon")).unwrap();
+
+    let schema: serde_json::Value = serde_json::from_str(&db.schema_json()).unwrap();
+    assert_eq!(schema, serde_json::json!({
+        "tables": [{
+            "name": "users",
+            "columns": [
+                { "name": "id", "type": "INT", "width": 10, "primary_key": true, "not_null": true, "default": null, "comment": null },
+                { "name": "name", "type": "VARCHAR", "width": 50, "primary_key": false, "not_null": false, "default": "anon", "comment": null },
+            ],
+        }],
+    }));
+}
+
+#[test]
+fn failed_save_leaves_previous_file_intact() {
+    let dir = std::env::temp_dir().join(format!("rustique_atomic_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.set_path(&db_path);
+    db.save().unwrap();
+    let before = std::fs::read_to_string(&db_path).unwrap();
+
+    // 临时文件路径被目录占用，写入失败
+    let tmp_path = dir.join("db.json.tmp");
+    std::fs::create_dir(&tmp_path).unwrap();
+    db.insert("users", None, vec![vec!["3", "Carol"]]).unwrap();
+    assert!(db.save().is_err());
+    assert_eq!(std::fs::read_to_string(&db_path).unwrap(), before);
+
+    // 再次正常保存时整体替换旧文件
+    std::fs::remove_dir(&tmp_path).unwrap();
+    db.save().unwrap();
+    assert!(!tmp_path.exists());
+    let loaded = Database::load_readonly_from(&db_path).unwrap();
+    assert_eq!(loaded.row_count("users"), Some(3));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn primary_key_lists_key_columns() {
+    let mut db = setup_users();
+    assert_eq!(db.primary_key("users"), Some(vec!["id".to_string()]));
+
+    db.create_table("enrollments", vec![
+        ("student", DataType::Int(10), true, true),
+        ("grade", DataType::Int(10), false, false),
+        ("course", DataType::Varchar(20), true, true),
+    ]).unwrap();
+    assert_eq!(db.primary_key("Enrollments"), Some(vec!["student".to_string(), "course".to_string()]));
+
+    db.create_table("log", vec![("msg", DataType::Varchar(50), false, false)]).unwrap();
+    assert_eq!(db.primary_key("log"), Some(Vec::new()));
+    assert_eq!(db.primary_key("missing"), None);
+}
+
+#[test]
+fn sample_returns_first_rows() {
+    let db = setup_users();
+    assert_eq!(db.sample("users", 1).unwrap(), vec![vec!["1", "Alice"]]);
+    assert_eq!(db.sample("USERS", 10).unwrap(), vec![vec!["1", "Alice"], vec!["2", "Bob"]]);
+    assert!(db.sample("users", 0).unwrap().is_empty());
+    assert_eq!(db.sample("missing", 1).unwrap_err(), "Table 'missing' not found");
+}
+
+#[test]
+fn random_sample_is_reproducible_and_bounded() {
+    let mut db = setup_users();
+    db.insert("users", None, vec![vec!["3", "Carol"], vec!["4", "Dave"], vec!["5", "Eve"]]).unwrap();
+
+    let sample = db.sample_random("users", 2, 42).unwrap();
+    assert_eq!(sample.len(), 2);
+    assert_eq!(sample, db.sample_random("users", 2, 42).unwrap());
+    // 结果保持原有行顺序
+    let ids: Vec<i64> = sample.iter().map(|row| row[0].parse().unwrap()).collect();
+    assert!(ids[0] < ids[1]);
+
+    assert_eq!(db.sample_random("users", 10, 7).unwrap().len(), 5);
+}
+
+#[test]
+fn column_comment_round_trips_and_appears_in_describe() {
+    let dir = std::env::temp_dir().join(format!("rustique_comment_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db_path = dir.join("db.json");
+
+    let mut db = setup_users();
+    db.set_column_comment("users", "ID", Some("primary id")).unwrap();
+    db.set_path(&db_path);
+    db.save().unwrap();
+
+    let loaded = Database::load_readonly_from(&db_path).unwrap();
+    assert_eq!(loaded.columns("users").unwrap()[0].comment.as_deref(), Some("primary id"));
+    assert_eq!(loaded.describe("users").unwrap(), vec![
+        vec!["id", "INT(10)", "PRI", "NO", "", "primary id"],
+        vec!["name", "VARCHAR(50)", "", "YES", "", ""],
+    ]);
+    assert!(loaded.describe("missing").is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn files_without_column_comments_still_load() {
+    let dir = std::env::temp_dir().join(format!("rustique_old_comment_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("db.json");
+    std::fs::write(&db_path, r#"{"tables":[{"name":"t","columns":[
+        {"name":"id","data_type":{"Int":10},"is_primary":true,"not_null":true}
+    ],"data":[["1"]]}]}"#).unwrap();
+
+    let loaded = Database::load_readonly_from(&db_path).unwrap();
+    assert_eq!(loaded.columns("t").unwrap()[0].comment, None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn column_stats_on_int_column_with_nulls() {
+    use rustique_db::database::NumericStats;
+
+    let mut db = Database::new();
+    db.create_table("scores", vec![
+        ("id", DataType::Int(10), true, true),
+        ("points", DataType::Int(10), false, false),
+        ("player", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("scores", None, vec![
+        vec!["1", "10", "a"],
+        vec!["2", "", "b"],
+        vec!["3", "-4", "c"],
+        vec!["4", "24", "d"],
+        vec!["5", "", "e"],
+    ]).unwrap();
+
+    assert_eq!(db.column_stats("scores", "POINTS").unwrap(), NumericStats {
+        column: "points".into(),
+        count: 3,
+        nulls: 2,
+        min: Some(-4),
+        max: Some(24),
+        avg: Some(10.0),
+    });
+
+    assert_eq!(db.column_stats("scores", "player").unwrap_err().to_string(), "Column 'player' is not numeric");
+    assert!(matches!(db.column_stats("missing", "points"), Err(DbError::TableNotFound(_))));
+    assert!(db.column_stats("scores", "nope").is_err());
+}
+
+#[test]
+fn column_stats_on_all_null_column() {
+    let mut db = Database::new();
+    db.create_table("t", vec![("n", DataType::Int(10), false, false)]).unwrap();
+    db.insert("t", None, vec![vec![""]]).unwrap();
+
+    let stats = db.column_stats("t", "n").unwrap();
+    assert_eq!((stats.count, stats.nulls), (0, 1));
+    assert_eq!((stats.min, stats.max, stats.avg), (None, None, None));
+}
+
+#[test]
+fn create_table_without_columns_is_rejected() {
+    let mut db = Database::new();
+    assert_eq!(db.create_table("empty", vec![]).unwrap_err(), "Table 'empty' must have at least one column");
+    assert!(!db.table_exists("empty"));
+}
+
+#[test]
+fn database_can_be_moved_to_another_thread() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Database>();
+
+    let mut db = setup_users();
+    db.register_function("shout", Box::new(|args: &[String]| args.concat().to_uppercase())).unwrap();
+    let db = std::thread::spawn(move || {
+        db.set_progress(Some((1, Box::new(|_| {}))));
+        db.update("users", vec![("name".into(), "x".into())], Some("id = 1")).unwrap();
+        db
+    }).join().unwrap();
+    assert_eq!(db.row_count("users"), Some(2));
+}
@@ -0,0 +1,57 @@
+use rustique_db::database::{DataType, Database};
+use rustique_db::error::DbError;
+
+mod common;
+use common::run;
+
+fn setup_users() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![vec!["1", "Alice"], vec!["2", "Bob"]]).unwrap();
+    db
+}
+
+#[test]
+fn delete_from_missing_table_is_table_not_found() {
+    let mut db = setup_users();
+    let err = db.delete("orders", None).unwrap_err();
+    assert!(matches!(err, DbError::TableNotFound(ref name) if name == "orders"), "{}", err);
+    assert_eq!(err.to_string(), "Table 'orders' not found");
+}
+
+#[test]
+fn delete_matching_no_rows_reports_zero() {
+    let mut db = setup_users();
+    assert_eq!(db.delete("users", Some("id = 99")).unwrap(), 0);
+    assert_eq!(db.row_count("users"), Some(2));
+}
+
+#[test]
+fn delete_with_invalid_condition_is_an_error() {
+    let mut db = setup_users();
+    let err = db.delete("users", Some("missing = 1")).unwrap_err();
+    assert!(matches!(err, DbError::Parse(_)), "{}", err);
+    assert_eq!(db.row_count("users"), Some(2));
+}
+
+#[test]
+fn delete_without_from_keyword() {
+    let mut db = setup_users();
+    assert_eq!(run(&mut db, "DELETE users WHERE id = 1").unwrap(), 1);
+    assert_eq!(db.row_count("users"), Some(1));
+}
+
+#[test]
+fn delete_with_table_alias() {
+    let mut db = setup_users();
+    assert_eq!(db.delete_with_alias("users", Some("u"), Some("u.id = 1")).unwrap(), 1);
+    // 有别名时未限定的列名仍然可用
+    assert_eq!(db.delete_with_alias("users", Some("u"), Some("name = 'Nobody'")).unwrap(), 0);
+    assert_eq!(db.row_count("users"), Some(1));
+
+    run(&mut db, "DELETE FROM users u WHERE u.name = 'Bob'").unwrap();
+    assert_eq!(db.row_count("users"), Some(0));
+}
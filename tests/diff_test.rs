@@ -0,0 +1,54 @@
+use rustique_db::database::{ColumnChange, DataType, Database, RowCountChange};
+
+fn base() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, false),
+    ]).unwrap();
+    db.create_table("legacy", vec![("id", DataType::Int(10), false, false)]).unwrap();
+    db.insert("users", None, vec![vec!["1", "Alice"]]).unwrap();
+    db
+}
+
+#[test]
+fn identical_databases_have_no_diff() {
+    let diff = base().diff(&base());
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "No differences\n");
+}
+
+#[test]
+fn diff_reports_tables_columns_and_row_counts() {
+    let old = base();
+    let mut new = Database::new();
+    new.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(100), false, false),
+        ("age", DataType::Int(10), false, false),
+    ]).unwrap();
+    new.create_table("orders", vec![("id", DataType::Int(10), true, true)]).unwrap();
+    new.insert("users", None, vec![vec!["1", "Alice", "30"], vec!["2", "Bob", "25"]]).unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added_tables, vec!["orders"]);
+    assert_eq!(diff.removed_tables, vec!["legacy"]);
+    assert_eq!(diff.column_changes, vec![
+        ColumnChange::Modified {
+            table: "users".into(),
+            column: "name".into(),
+            before: "VARCHAR(50)".into(),
+            after: "VARCHAR(100)".into(),
+        },
+        ColumnChange::Added { table: "users".into(), column: "age".into() },
+    ]);
+    assert_eq!(diff.row_count_changes, vec![RowCountChange { table: "users".into(), before: 1, after: 2 }]);
+
+    assert_eq!(diff.to_string(), "\
++ table orders
+- table legacy
+~ column users.name: VARCHAR(50) -> VARCHAR(100)
++ column users.age
+~ rows users: 1 -> 2
+");
+}
@@ -0,0 +1,73 @@
+use rustique_db::database::{DataType, Database};
+use rustique_db::split_statements;
+
+mod common;
+use common::run;
+
+fn setup() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, true),
+        ("note", DataType::Varchar(100), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![
+        vec!["1", "O'Brien", "back\\slash"],
+        vec!["2", "Bob", ""],
+    ]).unwrap();
+    db.create_table("order", vec![("id", DataType::Int(10), true, true)]).unwrap();
+    db
+}
+
+#[test]
+fn dump_quotes_identifiers_and_values() {
+    let sql = setup().to_sql();
+    assert_eq!(sql, concat!(
+        "CREATE TABLE \"users\" (\n",
+        "  \"id\" INT PRIMARY KEY,\n",
+        "  \"name\" VARCHAR(50) NOT NULL,\n",
+        "  \"note\" VARCHAR(100)\n",
+        ");\n",
+        "INSERT INTO \"users\" VALUES\n",
+        "  (1, 'O''Brien', 'back\\\\slash'),\n",
+        "  (2, 'Bob', NULL);\n",
+        "\n",
+        "CREATE TABLE \"order\" (\n",
+        "  \"id\" INT PRIMARY KEY\n",
+        ");\n",
+        "\n",
+    ));
+}
+
+#[test]
+fn dump_round_trips_into_fresh_database() {
+    let original = setup();
+
+    let mut restored = Database::new();
+    for stmt in split_statements(&original.to_sql()) {
+        run(&mut restored, &stmt).unwrap();
+    }
+
+    let tables: Vec<_> = original.tables_iter().map(|t| (&t.name, &t.columns, &t.data)).collect();
+    let restored_tables: Vec<_> = restored.tables_iter().map(|t| (&t.name, &t.columns, &t.data)).collect();
+    assert_eq!(tables, restored_tables);
+}
+
+#[test]
+fn create_statement_is_schema_only() {
+    let db = setup();
+    assert_eq!(db.create_statement("ORDER").unwrap(), "CREATE TABLE \"order\" (\n  \"id\" INT PRIMARY KEY\n);");
+    assert!(!db.create_statement("users").unwrap().contains("INSERT"));
+    assert!(db.create_statement("missing").is_none());
+}
+
+#[test]
+fn create_statement_reparses_into_same_schema() {
+    let mut db = setup();
+    db.set_column_default("users", "note", Some("n/a")).unwrap();
+    let ddl = db.create_statement("users").unwrap();
+
+    let mut copy = Database::new();
+    run(&mut copy, &ddl).unwrap();
+    assert_eq!(copy.columns("users"), db.columns("users"));
+}
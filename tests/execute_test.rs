@@ -0,0 +1,111 @@
+use rustique_db::database::{DataType, Database};
+use rustique_db::error::DbError;
+use rustique_db::{execute_single, split_statements, QueryResult};
+use rustique_db::repl::ReplSettings;
+
+mod common;
+use common::{run, run_select};
+
+#[test]
+fn semicolon_inside_string_literal_is_not_a_separator() {
+    let parts = split_statements("INSERT INTO notes VALUES ('a;b');");
+    assert_eq!(parts, vec!["INSERT INTO notes VALUES ('a;b')"]);
+}
+
+#[test]
+fn multiple_statements_with_quoted_semicolon_and_comments() {
+    let sql = "INSERT INTO notes VALUES ('x'); -- trailing; comment\n\
+               INSERT INTO notes VALUES ('it''s; fine'); /* block; */ SELECT * FROM notes;";
+    let parts = split_statements(sql);
+    assert_eq!(parts, vec![
+        "INSERT INTO notes VALUES ('x')",
+        "INSERT INTO notes VALUES ('it''s; fine')",
+        "SELECT * FROM notes",
+    ]);
+
+    let mut db = Database::new();
+    db.create_table("notes", vec![("body", DataType::Varchar(50), false, false)]).unwrap();
+    for stmt in &parts[..2] {
+        run(&mut db, stmt).unwrap();
+    }
+    let rows = run_select(&db, &parts[2]).unwrap();
+    assert_eq!(rows, vec![vec!["x".to_string()], vec!["it's; fine".to_string()]]);
+}
+
+#[test]
+fn comment_markers_inside_quotes_are_kept() {
+    let parts = split_statements("INSERT INTO notes VALUES ('--not a comment', '/*nor this*/')");
+    assert_eq!(parts, vec!["INSERT INTO notes VALUES ('--not a comment', '/*nor this*/')"]);
+}
+
+#[test]
+fn statements_report_their_starting_line() {
+    use rustique_db::split_statements_with_lines;
+
+    let sql = "-- header\nCREATE TABLE t (id INT);\n\n/* multi\nline */ INSERT INTO t\nVALUES (1); INSERT INTO t VALUES ('a\nb');\nSELECT * FROM t;";
+    let lines: Vec<usize> = split_statements_with_lines(sql).into_iter().map(|(line, _)| line).collect();
+    assert_eq!(lines, vec![2, 5, 6, 8]);
+}
+
+#[test]
+fn execute_single_runs_whole_input_as_one_statement() {
+    let mut db = Database::new();
+    db.create_table("notes", vec![("body", DataType::Varchar(50), false, false)]).unwrap();
+
+    assert!(execute_single("INSERT INTO notes VALUES ('a;b')", &mut db, &ReplSettings::default()));
+    let rows = db.select("notes", vec!["body"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["a;b".to_string()]]);
+
+    assert!(!execute_single("INSERT INTO missing VALUES ('x')", &mut db, &ReplSettings::default()));
+}
+
+#[test]
+fn apply_runs_batch_and_reports_each_result() {
+    let mut db = Database::new();
+    let results = db.apply(&[
+        "CREATE TABLE t (id INT PRIMARY KEY, name VARCHAR(10))",
+        "INSERT INTO t VALUES (1, 'a'), (2, 'b')",
+        "UPDATE t SET name = 'c' WHERE id = 2",
+        "SELECT name FROM t",
+    ], true);
+    let results: Vec<QueryResult> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results, vec![
+        QueryResult::Created,
+        QueryResult::Inserted(2),
+        QueryResult::Updated(1),
+        QueryResult::Rows {
+            headers: vec!["name".to_string()],
+            rows: vec![vec!["a".to_string()], vec!["c".to_string()]],
+        },
+    ]);
+}
+
+#[test]
+fn apply_stops_or_continues_after_failure() {
+    let batch = [
+        "INSERT INTO t VALUES (1)",
+        "INSERT INTO t VALUES (1)",
+        "INSERT INTO t VALUES (2)",
+    ];
+    let setup = || {
+        let mut db = Database::new();
+        db.create_table("t", vec![("id", DataType::Int(10), true, true)]).unwrap();
+        db
+    };
+
+    let mut db = setup();
+    let results = db.apply(&batch, true);
+    assert_eq!(results.len(), 2);
+    assert!(matches!(&results[1], Err(DbError::Execution(e)) if e.contains("Duplicate entry")));
+    assert_eq!(db.row_count("t"), Some(1));
+
+    let mut db = setup();
+    let results = db.apply(&batch, false);
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+    assert_eq!(results[2].as_ref().unwrap(), &QueryResult::Inserted(1));
+    assert_eq!(db.row_count("t"), Some(2));
+
+    // 语法错误单独报告为解析错误
+    assert!(matches!(db.apply(&["SELEC 1"], true)[0], Err(DbError::Parse(_))));
+}
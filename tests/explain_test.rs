@@ -0,0 +1,59 @@
+use rustique_db::database::{DataType, Database, PlanStep};
+use rustique_db::parser::SqlAst;
+
+fn setup_users() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, false),
+        ("age", DataType::Int(3), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![vec!["1", "Alice", "30"], vec!["2", "Bob", "20"]]).unwrap();
+    db
+}
+
+fn select(columns: &[&str], where_clause: Option<&str>, order_by: Vec<(&str, bool)>) -> SqlAst {
+    SqlAst::Select {
+        table: "users".into(),
+        alias: None,
+        joins: vec![],
+        columns: columns.iter().map(|c| c.to_string()).collect(),
+        aliases: vec![None; columns.len()],
+        where_clause: where_clause.map(String::from),
+        group_by: vec![],
+        order_by: order_by.into_iter().map(|(c, d)| (c.to_string(), d)).collect(),
+        limit: Some(10),
+        offset: None,
+        distinct_on: vec![],
+    }
+}
+
+#[test]
+fn plan_lists_scan_filter_sort_and_projection() {
+    let db = setup_users();
+    let ast = select(&["name", "age"], Some("age > 18"), vec![("name", true)]);
+    let plan = db.explain(&ast).unwrap();
+
+    let ops: Vec<&str> = plan.iter().map(|s| s.operation).collect();
+    assert_eq!(ops, vec!["SCAN", "FILTER", "SORT", "LIMIT", "PROJECT"]);
+    assert_eq!(plan[0].detail, "users (full scan, 2 rows)");
+    assert_eq!(plan[1], PlanStep { operation: "FILTER", detail: "age > 18".into() });
+    assert_eq!(plan[2].detail, "name DESC");
+    assert_eq!(plan[4].detail, "name, age");
+}
+
+#[test]
+fn plan_without_where_or_order_has_no_filter_or_sort() {
+    let db = setup_users();
+    let plan = db.explain(&select(&["COUNT(*)"], None, vec![])).unwrap();
+    let ops: Vec<&str> = plan.iter().map(|s| s.operation).collect();
+    assert_eq!(ops, vec!["SCAN", "GROUP", "LIMIT", "PROJECT"]);
+}
+
+#[test]
+fn explain_rejects_missing_tables_and_non_select() {
+    let db = Database::new();
+    assert_eq!(db.explain(&select(&["*"], None, vec![])).unwrap_err(), "Table 'users' not found");
+    let calc = SqlAst::Calculate { expression: "1 + 1".into(), result: 2.0 };
+    assert_eq!(db.explain(&calc).unwrap_err(), "EXPLAIN only supports SELECT");
+}
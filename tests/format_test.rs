@@ -0,0 +1,73 @@
+use std::fs;
+
+use rustique_db::database::{DataType, Database};
+use rustique_db::format::{format_table, group_int_columns, group_thousands, int_columns};
+use rustique_db::repl::{self, ReplSettings};
+
+#[test]
+fn format_table_without_headers_does_not_panic() {
+    assert_eq!(format_table(vec![], vec![]), "(no columns, 0 row(s))");
+    assert_eq!(format_table(vec![], vec![vec![], vec![]]), "(no columns, 2 row(s))");
+}
+
+#[test]
+fn zero_column_table_loaded_from_file_can_be_selected() {
+    let dir = std::env::temp_dir().join(format!("rustique_zero_cols_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("db.json");
+    fs::write(&path, r#"{"tables":[{"name":"t","columns":[],"data":[[]]}]}"#).unwrap();
+
+    let db = Database::load_readonly_from(&path).unwrap();
+    let rows = db.select("t", vec!["*"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![Vec::<String>::new()]);
+    assert_eq!(format_table(vec![], rows), "(no columns, 1 row(s))");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn group_thousands_across_magnitudes() {
+    let cases = [
+        ("0", "0"),
+        ("7", "7"),
+        ("999", "999"),
+        ("1000", "1,000"),
+        ("65536", "65,536"),
+        ("1000000", "1,000,000"),
+        ("-1", "-1"),
+        ("-1000", "-1,000"),
+        ("-123456789", "-123,456,789"),
+        ("9223372036854775807", "9,223,372,036,854,775,807"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(group_thousands(input), expected, "{}", input);
+    }
+    // 不是整数时原样返回
+    for input in ["", "-", "12.5", "abc", "1,000"] {
+        assert_eq!(group_thousands(input), input);
+    }
+}
+
+#[test]
+fn thousands_separator_only_affects_int_columns_in_output() {
+    let mut db = Database::new();
+    db.create_table("sales", vec![
+        ("amount", DataType::Int(20), false, false),
+        ("code", DataType::Varchar(10), false, false),
+    ]).unwrap();
+    db.insert("sales", None, vec![vec!["1234567", "1234567"]]).unwrap();
+
+    let mut settings = ReplSettings::default();
+    repl::handle_dot_command(".thousands on", &mut db, &mut settings);
+    assert!(settings.thousands_separator);
+
+    // 分组只在显示时进行，查询结果本身保持原值
+    let mut rows = db.select("sales", vec!["*"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["1234567".to_string(), "1234567".to_string()]]);
+    let is_int = int_columns(db.table("sales").unwrap(), &["*"]);
+    assert_eq!(is_int, vec![true, false]);
+    group_int_columns(&mut rows, &is_int);
+    assert_eq!(rows, vec![vec!["1,234,567".to_string(), "1234567".to_string()]]);
+    assert_eq!(int_columns(db.table("sales").unwrap(), &["code", "CONCAT(code, amount)", "amount"]), vec![false, false, true]);
+}
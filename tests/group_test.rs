@@ -0,0 +1,98 @@
+use rustique_db::database::{DataType, Database};
+
+mod common;
+use common::run_select;
+
+fn setup_sales() -> Database {
+    let mut db = Database::new();
+    db.create_table("sales", vec![
+        ("id", DataType::Int(10), true, true),
+        ("region", DataType::Varchar(20), false, false),
+        ("amount", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("sales", None, vec![
+        vec!["1", "north", "10"],
+        vec!["2", "south", "5"],
+        vec!["3", "north", "7"],
+        vec!["4", "east", ""],
+        vec!["5", "south", "20"],
+    ]).unwrap();
+    db
+}
+
+fn rows(values: &[&[&str]]) -> Vec<Vec<String>> {
+    values.iter().map(|r| r.iter().map(|v| v.to_string()).collect()).collect()
+}
+
+#[test]
+fn grouped_order_by_grouping_column() {
+    let db = setup_sales();
+    let result = db.select_grouped(
+        "sales",
+        vec!["region", "COUNT(*)", "SUM(amount)"],
+        None,
+        vec!["region"],
+        Some(vec![("region", true)]),
+        None,
+        None,
+    ).unwrap();
+    assert_eq!(result, rows(&[
+        &["south", "2", "25"],
+        &["north", "2", "17"],
+        &["east", "1", ""],
+    ]));
+}
+
+#[test]
+fn grouped_order_by_ungrouped_column_is_rejected() {
+    let db = setup_sales();
+    let err = run_select(&db, "SELECT region, COUNT(*) FROM sales GROUP BY region ORDER BY amount").unwrap_err();
+    assert!(err.contains("ORDER BY column 'amount'"), "{}", err);
+
+    let err = run_select(&db, "SELECT region, amount FROM sales GROUP BY region").unwrap_err();
+    assert!(err.contains("Column 'amount' must appear in GROUP BY"), "{}", err);
+}
+
+#[test]
+fn grouped_order_by_from_sql() {
+    let db = setup_sales();
+    let result = run_select(&db, "SELECT region, MAX(amount) FROM sales GROUP BY region ORDER BY region").unwrap();
+    assert_eq!(result, rows(&[&["east", ""], &["north", "10"], &["south", "20"]]));
+}
+
+#[test]
+fn aggregate_without_group_by_is_single_group() {
+    let db = setup_sales();
+    let result = db.select("sales", vec!["COUNT(*)", "COUNT(amount)", "MIN(amount)"], None, None, None, None).unwrap();
+    assert_eq!(result, rows(&[&["5", "4", "5"]]));
+
+    let result = db.select("sales", vec!["COUNT(*)"], Some("amount > 100"), None, None, None).unwrap();
+    assert_eq!(result, rows(&[&["0"]]));
+}
+
+#[test]
+fn group_by_without_aggregate_returns_distinct_keys() {
+    let db = setup_sales();
+    let result = db.select_grouped("sales", vec!["region"], None, vec!["region"], None, None, None).unwrap();
+    // 按首次出现的顺序，每个分组一行
+    assert_eq!(result, rows(&[&["north"], &["south"], &["east"]]));
+
+    let filtered = db.select_grouped(
+        "sales", vec!["region"], Some("amount > 6"), vec!["region"], Some(vec![("region", false)]), None, None,
+    ).unwrap();
+    assert_eq!(filtered, rows(&[&["north"], &["south"]]));
+}
+
+#[test]
+fn order_groups_by_count_descending() {
+    let mut db = setup_sales();
+    db.insert("sales", None, vec![vec!["6", "south", "1"]]).unwrap();
+
+    let result = db.select_grouped(
+        "sales", vec!["region", "COUNT(*)"], None, vec!["region"], Some(vec![("count( * )", true)]), None, None,
+    ).unwrap();
+    assert_eq!(result, rows(&[&["south", "3"], &["north", "2"], &["east", "1"]]));
+
+    let result = run_select(&db, "SELECT region, COUNT(*) c FROM sales GROUP BY region ORDER BY c DESC").unwrap();
+    assert_eq!(result, rows(&[&["south", "3"], &["north", "2"], &["east", "1"]]));
+}
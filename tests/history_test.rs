@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rustique_db::database::Database;
+use rustique_db::history::CommandHistory;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustique_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn load_with_existing_history_file() {
+    let dir = temp_dir("history_existing");
+    let history_path = dir.join("history.json");
+
+    let mut saved = CommandHistory::new(10);
+    saved.add("SELECT * FROM users;");
+    saved.add("SELECT name\nFROM users;");
+    saved.save_to(&history_path).unwrap();
+
+    let mut history = CommandHistory::new(10);
+    let db = Database::load_with_history_from(&dir.join("db.json"), &history_path, &mut history).unwrap();
+
+    assert_eq!(db.tables_iter().count(), 0);
+    let commands: Vec<&String> = history.iter().collect();
+    assert_eq!(commands, vec!["SELECT * FROM users;", "SELECT name\nFROM users;"]);
+
+    drop(db);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn load_without_any_files_present() {
+    let dir = temp_dir("history_missing");
+
+    let mut history = CommandHistory::new(10);
+    let db = Database::load_with_history_from(
+        &dir.join("db.json"),
+        &dir.join("history.json"),
+        &mut history,
+    ).unwrap();
+
+    assert_eq!(db.tables_iter().count(), 0);
+    assert_eq!(history.len(), 0);
+    assert_eq!(db.path(), dir.join("db.json"));
+
+    drop(db);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn find_last_command_by_prefix() {
+    let mut history = CommandHistory::new(10);
+    history.add("SELECT * FROM users;");
+    history.add("INSERT INTO users VALUES (1, 'Alice');");
+    history.add("select name FROM users;");
+
+    // 多个候选时取最近的一条，且不区分大小写
+    assert_eq!(history.find_last_starting_with("sel"), Some((2, "select name FROM users;".to_string())));
+    assert_eq!(history.find_last_starting_with("INS"), Some((1, "INSERT INTO users VALUES (1, 'Alice');".to_string())));
+    assert_eq!(history.find_last_starting_with("drop"), None);
+    assert_eq!(history.find_last_starting_with(""), None);
+}
+
+#[test]
+fn prefix_history_commands_are_not_recorded() {
+    let mut history = CommandHistory::new(10);
+    history.add("SELECT 1;");
+    history.add("!sel;");
+    history.add("!0");
+    history.add("!!");
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn search_matches_substrings_case_insensitively() {
+    let mut history = CommandHistory::new(10);
+    history.add("SELECT * FROM users;");
+    history.add("INSERT INTO orders VALUES (1);");
+    history.add("select name from Users where id = 1;");
+
+    assert_eq!(history.search("users"), vec![
+        (0, "SELECT * FROM users;"),
+        (2, "select name from Users where id = 1;"),
+    ]);
+    assert_eq!(history.search("ORDERS"), vec![(1, "INSERT INTO orders VALUES (1);")]);
+    assert!(history.search("products").is_empty());
+    assert!(history.search("").is_empty());
+}
+
+#[test]
+fn set_max_size_grows_and_trims_oldest() {
+    let mut history = CommandHistory::new(2);
+    history.add("SELECT 1;");
+    history.add("SELECT 2;");
+    history.add("SELECT 3;");
+    assert_eq!(history.iter().collect::<Vec<_>>(), vec!["SELECT 2;", "SELECT 3;"]);
+
+    history.set_max_size(4);
+    history.add("SELECT 4;");
+    history.add("SELECT 5;");
+    assert_eq!(history.len(), 4);
+
+    history.set_max_size(2);
+    assert_eq!(history.max_size(), 2);
+    assert_eq!(history.iter().collect::<Vec<_>>(), vec!["SELECT 4;", "SELECT 5;"]);
+
+    history.add("SELECT 6;");
+    assert_eq!(history.iter().collect::<Vec<_>>(), vec!["SELECT 5;", "SELECT 6;"]);
+}
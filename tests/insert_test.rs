@@ -0,0 +1,307 @@
+use rustique_db::database::{DataType, Database};
+use rustique_db::error::DbError;
+use rustique_db::QueryResult;
+
+mod common;
+use common::run;
+
+fn setup_notes() -> Database {
+    let mut db = Database::new();
+    db.create_table("notes", vec![
+        ("body", DataType::Varchar(100), false, false),
+    ]).unwrap();
+    db
+}
+
+#[test]
+fn doubled_single_quote_is_unescaped() {
+    let mut db = setup_notes();
+    run(&mut db, "INSERT INTO notes VALUES ('it''s fine')").unwrap();
+
+    let rows = db.select("notes", vec!["body"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["it's fine".to_string()]]);
+}
+
+#[test]
+fn backslash_escapes_are_unescaped() {
+    let mut db = setup_notes();
+    run(&mut db, r#"INSERT INTO notes VALUES ('say \'hi\''), ('a\\b')"#).unwrap();
+
+    let rows = db.select("notes", vec!["body"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["say 'hi'".to_string()],
+        vec!["a\\b".to_string()],
+    ]);
+}
+
+#[test]
+fn all_constraint_violations_reported_together() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(5), false, true),
+        ("email", DataType::Varchar(20), false, true),
+        ("age", DataType::Int(10), false, false),
+    ]).unwrap();
+
+    let err = db.insert(
+        "users",
+        Some(vec!["id".to_string(), "name".to_string(), "age".to_string()]),
+        vec![vec!["1", "Bartholomew", "old"]],
+    ).unwrap_err();
+
+    let DbError::Violations(violations) = err else { panic!("{}", err) };
+    assert_eq!(violations.len(), 3, "{:?}", violations);
+    assert!(violations.contains(&"Value too long for column 'name' (max 5)".to_string()), "{:?}", violations);
+    assert!(violations.contains(&"Column 'email' cannot be null".to_string()), "{:?}", violations);
+    assert!(violations.contains(&"Value 'old' is not INT for column 'age'".to_string()), "{:?}", violations);
+    assert_eq!(db.row_count("users"), Some(0));
+}
+
+#[test]
+fn quoted_null_string_is_stored_literally() {
+    let mut db = setup_notes();
+    run(&mut db, "INSERT INTO notes VALUES ('NULL'), (NULL), ('null')").unwrap();
+
+    let rows = db.select("notes", vec!["body"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["NULL".to_string()],
+        vec!["".to_string()],
+        vec!["null".to_string()],
+    ]);
+
+    let nulls = db.select("notes", vec!["body"], Some("body IS NULL"), None, None, None).unwrap();
+    assert_eq!(nulls.len(), 1);
+}
+
+#[test]
+fn api_insert_keeps_null_text() {
+    let mut db = setup_notes();
+    db.insert("notes", None, vec![vec!["NULL"], vec![""]]).unwrap();
+
+    let rows = db.select("notes", vec!["body"], Some("body = 'NULL'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["NULL".to_string()]]);
+    let rows = db.select("notes", vec!["body"], Some("body = NULL"), None, None, None).unwrap();
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn omitted_and_provided_columns_follow_null_and_default_rules() {
+    let mut db = Database::new();
+    db.create_table("accounts", vec![
+        ("id", DataType::Int(10), true, true),
+        ("nickname", DataType::Varchar(20), false, false),
+        ("email", DataType::Varchar(50), false, true),
+        ("status", DataType::Varchar(10), false, true),
+    ]).unwrap();
+    db.set_column_default("accounts", "status", Some("active")).unwrap();
+    let cols = |names: &[&str]| Some(names.iter().map(|n| n.to_string()).collect());
+
+    // 可空列：省略为 NULL，提供时保存提供的值
+    db.insert("accounts", cols(&["id", "email"]), vec![vec!["1", "a@x"]]).unwrap();
+    db.insert("accounts", cols(&["id", "email", "nickname"]), vec![vec!["2", "b@x", "bee"]]).unwrap();
+
+    // 非空列：没有默认值时省略报错，有默认值时使用默认值，提供时覆盖默认值
+    let err = db.insert("accounts", cols(&["id"]), vec![vec!["3"]]).unwrap_err().to_string();
+    assert!(err.contains("Column 'email' cannot be null"), "{}", err);
+    db.insert("accounts", cols(&["id", "email", "status"]), vec![vec!["4", "d@x", "banned"]]).unwrap();
+
+    let rows = db.select("accounts", vec!["id", "nickname", "status"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["1".to_string(), "".to_string(), "active".to_string()],
+        vec!["2".to_string(), "bee".to_string(), "active".to_string()],
+        vec!["4".to_string(), "".to_string(), "banned".to_string()],
+    ]);
+}
+
+#[test]
+fn default_declared_in_create_table() {
+    let mut db = Database::new();
+    run(&mut db, "CREATE TABLE tasks (id INT PRIMARY KEY, state VARCHAR(10) NOT NULL DEFAULT 'todo', note VARCHAR(20))").unwrap();
+    run(&mut db, "INSERT INTO tasks (id) VALUES (1)").unwrap();
+
+    let rows = db.select("tasks", vec!["state", "note"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["todo".to_string(), "".to_string()]]);
+    assert!(db.set_column_default("tasks", "id", Some("abc")).is_err());
+}
+
+#[test]
+fn default_keyword_uses_column_default() {
+    let mut db = Database::new();
+    run(&mut db, "CREATE TABLE users (id INT PRIMARY KEY, status VARCHAR(10) NOT NULL DEFAULT 'new', bio VARCHAR(20), code VARCHAR(5) NOT NULL)").unwrap();
+    run(&mut db, "INSERT INTO users (id, status, bio, code) VALUES (1, DEFAULT, DEFAULT, 'a')").unwrap();
+    run(&mut db, "INSERT INTO users VALUES (2, 'DEFAULT', 'x', 'b')").unwrap();
+
+    let rows = db.select("users", vec!["status", "bio"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["new".to_string(), "".to_string()],
+        vec!["DEFAULT".to_string(), "x".to_string()],
+    ]);
+
+    // NOT NULL 且没有默认值的列不能使用 DEFAULT
+    let err = run(&mut db, "INSERT INTO users VALUES (3, 'ok', NULL, DEFAULT)").unwrap_err();
+    assert!(err.contains("Column 'code' cannot be null"), "{}", err);
+}
+
+#[test]
+fn default_value_through_api() {
+    use rustique_db::database::DEFAULT_VALUE;
+
+    let mut db = setup_notes();
+    db.set_column_default("notes", "body", Some("empty")).unwrap();
+    db.insert("notes", None, vec![vec![DEFAULT_VALUE]]).unwrap();
+
+    let rows = db.select("notes", vec!["body"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["empty".to_string()]]);
+}
+
+#[test]
+fn int_values_are_trimmed_on_insert() {
+    let mut db = Database::new();
+    db.create_table("people", vec![
+        ("id", DataType::Int(10), true, true),
+        ("age", DataType::Int(10), false, false),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("people", None, vec![vec![" 1", " 30 ", " Ann "], vec!["2 ", "\t25", "Bob"]]).unwrap();
+
+    let rows = db.select("people", vec!["id", "age", "name"], Some("age = 30"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["1".to_string(), "30".to_string(), " Ann ".to_string()]]);
+
+    let rows = db.select("people", vec!["name"], Some("id = 2"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["Bob".to_string()]]);
+
+    // VARCHAR 保持原样，比较也是精确的
+    let rows = db.select("people", vec!["id"], Some("name = 'Ann'"), None, None, None).unwrap();
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn trailing_values_may_be_omitted_for_nullable_columns() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, true),
+        ("email", DataType::Varchar(50), false, false),
+        ("role", DataType::Varchar(10), false, true),
+    ]).unwrap();
+    db.set_column_default("users", "role", Some("member")).unwrap();
+
+    db.insert("users", None, vec![vec!["1", "Alice"]]).unwrap();
+    let rows = db.select("users", vec!["*"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["1".to_string(), "Alice".to_string(), "".to_string(), "member".to_string()]]);
+
+    // 省略的末尾列中有 NOT NULL 且没有默认值的列时报错
+    let err = db.insert("users", None, vec![vec!["2"]]).unwrap_err().to_string();
+    assert!(err.contains("Column 'name' cannot be null"), "{}", err);
+
+    let err = db.insert("users", None, vec![vec!["3", "Bob", "b@x", "admin", "extra"]]).unwrap_err().to_string();
+    assert!(err.contains("Column count mismatch"), "{}", err);
+    assert_eq!(db.row_count("users"), Some(1));
+}
+
+#[test]
+fn duplicate_insert_column_is_rejected() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+
+    let err = db.insert("users", Some(vec!["id".into(), "ID".into()]), vec![vec!["1", "2"]]).unwrap_err().to_string();
+    assert_eq!(err, "Column 'ID' specified more than once");
+    assert_eq!(db.row_count("users"), Some(0));
+}
+
+#[test]
+fn trim_varchar_strips_padding_on_insert() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(5), false, false),
+    ]).unwrap();
+
+    db.insert("users", None, vec![vec!["1", "  Alice  "]]).unwrap_err();  // 未开启时按原样检查长度
+    db.set_trim_varchar(true);
+    db.insert("users", None, vec![vec!["1", "  Alice  "], vec!["2", "   "]]).unwrap();
+
+    let rows = db.select("users", vec!["name"], Some("name = 'Alice'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["Alice".to_string()]]);
+    assert!(db.scan("users").unwrap().nth(1).unwrap()[1].is_null());
+}
+
+#[test]
+fn varchar_length_counts_characters_not_bytes() {
+    let mut db = Database::new();
+    db.create_table("words", vec![
+        ("id", DataType::Int(10), true, true),
+        ("word", DataType::Varchar(3), false, false),
+    ]).unwrap();
+
+    // 3 个字符、9 个字节
+    db.insert("words", None, vec![vec!["1", "数据库"]]).unwrap();
+    let err = db.insert("words", None, vec![vec!["2", "数据库表"]]).unwrap_err().to_string();
+    assert_eq!(err, "Value too long for column 'word' (max 3)");
+    assert_eq!(db.select("words", vec!["word"], None, None, None, None).unwrap(), vec![vec!["数据库"]]);
+}
+
+#[test]
+fn insert_returning_includes_applied_defaults() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, true),
+        ("role", DataType::Varchar(10), false, false),
+    ]).unwrap();
+    db.set_column_default("users", "role", Some("member")).unwrap();
+    db.insert("users", None, vec![vec!["1", "Zed", "admin"]]).unwrap();
+
+    let cols = Some(vec!["id".to_string(), "name".to_string()]);
+    let rows = db.insert_returning("users", cols, vec![vec!["2", "Alice"], vec!["3", "Bob"]], vec!["*"]).unwrap();
+    assert_eq!(rows, vec![
+        vec!["2".to_string(), "Alice".to_string(), "member".to_string()],
+        vec!["3".to_string(), "Bob".to_string(), "member".to_string()],
+    ]);
+    assert_eq!(db.row_count("users"), Some(3));
+}
+
+#[test]
+fn insert_returning_clause_is_formatted_like_select() {
+    let mut db = Database::new();
+    run(&mut db, "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR(20), role VARCHAR(10) DEFAULT 'member')").unwrap();
+
+    let results = db.apply(&["INSERT INTO users (id, name) VALUES (1, 'Alice') RETURNING id, role AS r"], true);
+    assert_eq!(results[0].as_ref().unwrap(), &QueryResult::Rows {
+        headers: vec!["id".to_string(), "r".to_string()],
+        rows: vec![vec!["1".to_string(), "member".to_string()]],
+    });
+
+    // 失败的插入不返回任何行
+    assert!(db.apply(&["INSERT INTO users (id) VALUES (1) RETURNING *"], true)[0].is_err());
+}
+
+#[test]
+fn int_values_are_canonicalized_on_write() {
+    let mut db = Database::new();
+    db.create_table("codes", vec![
+        ("id", DataType::Int(10), true, true),
+        ("n", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("codes", None, vec![vec!["007", "010"], vec!["8", " 9 "], vec!["+10", "-003"]]).unwrap();
+
+    // 前导零和正号不保留，比较和排序按数值进行
+    let rows = db.select("codes", vec!["id", "n"], None, Some(vec![("id", false)]), None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["7".to_string(), "10".to_string()],
+        vec!["8".to_string(), "9".to_string()],
+        vec!["10".to_string(), "-3".to_string()],
+    ]);
+    assert_eq!(db.select("codes", vec!["id"], Some("id = 7"), None, None, None).unwrap(), vec![vec!["7".to_string()]]);
+    assert_eq!(db.select("codes", vec!["id"], Some("id = '007'"), None, None, None).unwrap().len(), 1);
+
+    // 007 与 7 是同一个主键
+    let err = db.insert("codes", None, vec![vec!["7", "1"]]).unwrap_err().to_string();
+    assert!(err.contains("Duplicate entry"), "{}", err);
+    db.update("codes", vec![("n".into(), "0042".into())], Some("id = 8")).unwrap();
+    assert_eq!(db.select("codes", vec!["n"], Some("n = 42"), None, None, None).unwrap(), vec![vec!["42".to_string()]]);
+}
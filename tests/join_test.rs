@@ -0,0 +1,132 @@
+use rustique_db::database::{DataType, Database, Join, JoinCondition, JoinKind};
+
+mod common;
+use common::run_select;
+
+fn setup() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("region", DataType::Varchar(10), false, false),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![
+        vec!["1", "eu", "Alice"],
+        vec!["2", "us", "Bob"],
+        vec!["3", "eu", "Carol"],
+    ]).unwrap();
+
+    db.create_table("orders", vec![
+        ("id", DataType::Int(10), true, true),
+        ("user_id", DataType::Int(10), false, false),
+        ("region", DataType::Varchar(10), false, false),
+        ("total", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("orders", None, vec![
+        vec!["10", "1", "eu", "100"],
+        vec!["11", "1", "us", "200"],
+        vec!["12", "2", "us", "300"],
+        vec!["13", "3", "eu", "400"],
+    ]).unwrap();
+    db
+}
+
+fn rows(values: &[&[&str]]) -> Vec<Vec<String>> {
+    values.iter().map(|r| r.iter().map(|v| v.to_string()).collect()).collect()
+}
+
+#[test]
+fn join_on_two_columns() {
+    let db = setup();
+    let joins = vec![Join {
+        kind: JoinKind::Inner,
+        table: "orders".into(),
+        alias: Some("o".into()),
+        condition: JoinCondition::On(vec![
+            ("u.id".into(), "o.user_id".into()),
+            ("u.region".into(), "o.region".into()),
+        ]),
+    }];
+    let joined = db.joined_table("users", Some("u"), &joins).unwrap();
+    let result = db.select_from(&joined, vec!["name", "total"], None, None, None, None).unwrap();
+    assert_eq!(result, rows(&[&["Alice", "100"], &["Bob", "300"], &["Carol", "400"]]));
+}
+
+#[test]
+fn join_on_two_columns_from_sql() {
+    let db = setup();
+    let result = run_select(&db,
+        "SELECT u.name, o.total FROM users u JOIN orders o ON u.id = o.user_id AND u.region = o.region",
+    ).unwrap();
+    assert_eq!(result, rows(&[&["Alice", "100"], &["Bob", "300"], &["Carol", "400"]]));
+}
+
+#[test]
+fn join_using_collapses_shared_column() {
+    let mut db = setup();
+    db.create_table("profiles", vec![
+        ("id", DataType::Int(10), true, true),
+        ("bio", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("profiles", None, vec![vec!["1", "hi"], vec!["3", "yo"]]).unwrap();
+
+    let joins = vec![Join {
+        kind: JoinKind::Inner,
+        table: "profiles".into(),
+        alias: None,
+        condition: JoinCondition::Using(vec!["id".into()]),
+    }];
+    let joined = db.joined_table("users", None, &joins).unwrap();
+    let names: Vec<&str> = joined.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["users.id", "users.region", "users.name", "profiles.bio"]);
+
+    let result = db.select_from(&joined, vec!["*"], None, None, None, None).unwrap();
+    assert_eq!(result, rows(&[&["1", "eu", "Alice", "hi"], &["3", "eu", "Carol", "yo"]]));
+
+    // 合并后的 id 不再有歧义
+    let ids = db.select_from(&joined, vec!["id"], None, None, None, None).unwrap();
+    assert_eq!(ids, rows(&[&["1"], &["3"]]));
+}
+
+#[test]
+fn join_using_from_sql() {
+    let db = setup();
+    let result = run_select(&db, "SELECT name, total FROM users JOIN orders USING (region) WHERE total > 250").unwrap();
+    assert_eq!(result, rows(&[&["Alice", "400"], &["Bob", "300"], &["Carol", "400"]]));
+}
+
+#[test]
+fn left_join_fills_unmatched_rows_with_nulls() {
+    let mut db = setup();
+    db.insert("users", None, vec![vec!["4", "us", "Dave"]]).unwrap();
+
+    let joins = vec![Join {
+        kind: JoinKind::Left,
+        table: "orders".into(),
+        alias: Some("o".into()),
+        condition: JoinCondition::On(vec![("u.id".into(), "o.user_id".into())]),
+    }];
+    let joined = db.joined_table("users", Some("u"), &joins).unwrap();
+    let result = db.select_from(&joined, vec!["name", "o.id", "total"], None, None, None, None).unwrap();
+    assert_eq!(result, rows(&[
+        &["Alice", "10", "100"],
+        &["Alice", "11", "200"],
+        &["Bob", "12", "300"],
+        &["Carol", "13", "400"],
+        &["Dave", "", ""],
+    ]));
+
+    // 右表列为 NULL，可以用 IS NULL 过滤出未匹配的行
+    let unmatched = db.select_from(&joined, vec!["name"], Some("total IS NULL"), None, None, None).unwrap();
+    assert_eq!(unmatched, rows(&[&["Dave"]]));
+}
+
+#[test]
+fn left_join_from_sql() {
+    let mut db = setup();
+    db.insert("users", None, vec![vec!["4", "us", "Dave"]]).unwrap();
+    let result = run_select(&db,
+        "SELECT u.name, o.total FROM users u LEFT JOIN orders o ON u.id = o.user_id AND u.region = o.region",
+    ).unwrap();
+    assert_eq!(result, rows(&[&["Alice", "100"], &["Bob", "300"], &["Carol", "400"], &["Dave", ""]]));
+}
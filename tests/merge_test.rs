@@ -0,0 +1,58 @@
+use rustique_db::database::{DataType, Database};
+use rustique_db::error::DbError;
+
+fn users_db(rows: Vec<Vec<&str>>) -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, false),
+    ]).unwrap();
+    db.insert("users", None, rows).unwrap();
+    db
+}
+
+fn ids(db: &Database, table: &str) -> Vec<String> {
+    db.scan(table).unwrap().map(|row| row[0].to_string()).collect()
+}
+
+#[test]
+fn merge_appends_rows_and_adds_new_tables() {
+    let mut db = users_db(vec![vec!["1", "Alice"]]);
+    let mut other = users_db(vec![vec!["2", "Bob"], vec!["3", "Carol"]]);
+    other.create_table("orders", vec![("id", DataType::Int(10), true, true)]).unwrap();
+    other.insert("orders", None, vec![vec!["10"]]).unwrap();
+
+    db.merge(other, false).unwrap();
+    assert_eq!(ids(&db, "users"), vec!["1", "2", "3"]);
+    assert_eq!(ids(&db, "orders"), vec!["10"]);
+}
+
+#[test]
+fn merge_conflicting_primary_key_errors_or_skips() {
+    let mut db = users_db(vec![vec!["1", "Alice"], vec!["2", "Bob"]]);
+    let other = users_db(vec![vec!["2", "Robert"], vec!["3", "Carol"]]);
+
+    let err = db.merge(other, false).unwrap_err();
+    assert!(matches!(err, DbError::DuplicateKey(ref t, ref k) if t == "users" && k == "2"), "{}", err);
+    assert_eq!(ids(&db, "users"), vec!["1", "2"]);
+
+    let other = users_db(vec![vec!["2", "Robert"], vec!["3", "Carol"]]);
+    db.merge(other, true).unwrap();
+    let rows = db.select("users", vec!["id", "name"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["1".to_string(), "Alice".to_string()],
+        vec!["2".to_string(), "Bob".to_string()],
+        vec!["3".to_string(), "Carol".to_string()],
+    ]);
+}
+
+#[test]
+fn merge_rejects_schema_mismatch() {
+    let mut db = users_db(vec![vec!["1", "Alice"]]);
+    let mut other = Database::new();
+    other.create_table("USERS", vec![("id", DataType::Int(10), true, true)]).unwrap();
+
+    let err = db.merge(other, true).unwrap_err();
+    assert!(matches!(err, DbError::SchemaMismatch(_)), "{}", err);
+    assert_eq!(ids(&db, "users"), vec!["1"]);
+}
@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rustique_db::database::{DataType, Database};
+use rustique_db::history::CommandHistory;
+use rustique_db::repl::{self, ReplSettings};
+
+// 每个测试使用独立的临时目录，避免写入 data/db.json
+fn temp_db_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustique_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir.join("db.json")
+}
+
+#[test]
+fn autosave_off_defers_writes_until_save() {
+    let path = temp_db_path("autosave");
+    let mut db = Database::new();
+    db.set_path(&path);
+    let mut settings = ReplSettings::default();
+
+    repl::handle_dot_command(".autosave off", &mut db, &mut settings);
+    assert!(!settings.autosave);
+
+    repl::run_input("CREATE TABLE t (id INT);", &mut db, &settings);
+    assert!(!path.exists(), "no file should be written while autosave is off");
+
+    repl::handle_dot_command(".save", &mut db, &mut settings);
+    assert!(path.exists());
+
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn autosave_on_writes_after_each_input() {
+    let path = temp_db_path("autosave_on");
+    let mut db = Database::new();
+    db.set_path(&path);
+    let settings = ReplSettings::default();
+
+    repl::run_input("CREATE TABLE t (id INT);", &mut db, &settings);
+    assert!(path.exists());
+
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn source_runs_each_statement_in_file() {
+    let path = temp_db_path("source");
+    let dir = path.parent().unwrap().to_path_buf();
+    fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("schema.sql");
+    fs::write(&script, "-- schema\n\
+        CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR(20));\n\
+        INSERT INTO users VALUES (1, 'Alice'), (2, 'Bob');\n\
+        INSERT INTO missing VALUES (1);\n\
+        CREATE TABLE tags (name VARCHAR(10));\n").unwrap();
+
+    let mut db = Database::new();
+    db.set_path(&path);
+    let settings = ReplSettings { autosave: false, ..ReplSettings::default() };
+
+    let (ok, failed) = repl::source_file(&script, &mut db, &settings).unwrap();
+    assert_eq!((ok, failed), (3, 1));
+    assert_eq!(db.row_count("users"), Some(2));
+    assert!(db.table_exists("tags"));
+
+    assert!(repl::source_file(&dir.join("nope.sql"), &mut db, &settings).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn delete_and_update_without_where_need_confirmation() {
+    use rustique_db::parser::SqlAst;
+
+    let delete_all = SqlAst::Delete { table: "users".into(), alias: None, where_clause: None };
+    let delete_one = SqlAst::Delete { table: "users".into(), alias: None, where_clause: Some("id = 1".into()) };
+    let update_all = SqlAst::Update { table: "users".into(), alias: None, set: vec![("age".into(), "1".into())], where_clause: None };
+    let update_one = SqlAst::Update {
+        table: "users".into(),
+        alias: None,
+        set: vec![("age".into(), "1".into())],
+        where_clause: Some("id = 1".into()),
+    };
+    let drop = SqlAst::Drop { tables: vec!["users".into()], if_exists: false };
+
+    assert_eq!(repl::affects_all_rows(&delete_all), Some("users"));
+    assert_eq!(repl::affects_all_rows(&update_all), Some("users"));
+    assert_eq!(repl::affects_all_rows(&delete_one), None);
+    assert_eq!(repl::affects_all_rows(&update_one), None);
+    assert_eq!(repl::affects_all_rows(&drop), None);
+}
+
+#[test]
+fn force_mode_toggles() {
+    let mut db = Database::new();
+    let mut settings = ReplSettings::default();
+    assert!(!settings.force);
+    repl::handle_dot_command(".force on", &mut db, &mut settings);
+    assert!(settings.force);
+    repl::handle_dot_command(".force off", &mut db, &mut settings);
+    assert!(!settings.force);
+}
+
+#[test]
+fn elapsed_time_is_formatted_in_milliseconds() {
+    use std::time::Duration;
+
+    assert_eq!(repl::format_elapsed(Duration::from_micros(420)), "(0.42 ms)");
+    assert_eq!(repl::format_elapsed(Duration::from_millis(1500)), "(1500.00 ms)");
+    assert_eq!(repl::format_elapsed(Duration::ZERO), "(0.00 ms)");
+}
+
+#[test]
+fn save_on_shutdown_writes_database_and_history() {
+    let db_path = temp_db_path("shutdown");
+    let history_path = db_path.with_file_name("history.json");
+
+    let mut db = Database::new();
+    db.set_path(&db_path);
+    db.create_table("t", vec![("id", DataType::Int(10), true, true)]).unwrap();
+    let mut history = CommandHistory::new(10);
+    history.add("CREATE TABLE t (id INT PRIMARY KEY);");
+    assert!(!db_path.exists());
+
+    repl::save_on_shutdown(&db, &history, &history_path).unwrap();
+
+    let mut reloaded_history = CommandHistory::new(10);
+    let reloaded = Database::load_with_history_from(&db_path, &history_path, &mut reloaded_history).unwrap();
+    assert!(reloaded.table_exists("t"));
+    assert_eq!(reloaded_history.len(), 1);
+
+    drop(reloaded);
+    let _ = fs::remove_dir_all(db_path.parent().unwrap());
+}
+
+#[test]
+fn peek_shows_at_most_five_rows() {
+    let mut db = Database::new();
+    db.create_table("t", vec![("id", DataType::Int(10), true, true)]).unwrap();
+    let rows: Vec<String> = (1..=7).map(|i| i.to_string()).collect();
+    db.insert("t", None, rows.iter().map(|r| vec![r.as_str()]).collect()).unwrap();
+
+    let output = repl::peek(&db, "t").unwrap();
+    assert!(output.lines().next().unwrap().contains("id"));
+    assert_eq!(output.lines().count(), 2 + repl::PEEK_ROWS);
+    assert!(output.contains(" 5 ") && !output.contains(" 6 "));
+
+    assert!(repl::peek(&db, "missing").is_err());
+}
+
+#[test]
+fn backslash_c_discards_buffered_input() {
+    let mut buffer = repl::InputBuffer::default();
+    assert!(!buffer.push_line("SELECT *\n"));
+    assert!(!buffer.push_line("FROM users\n"));
+    assert_eq!(buffer.as_str(), "SELECT *\nFROM users\n");
+
+    assert!(buffer.push_line("\\c\n"));
+    assert_eq!(buffer.as_str(), "");
+
+    assert!(!buffer.push_line("SELECT 1;\n"));
+    assert_eq!(buffer.as_str(), "SELECT 1;\n");
+}
+
+#[test]
+fn two_blank_lines_in_a_row_discard_buffered_input() {
+    let mut buffer = repl::InputBuffer::default();
+    buffer.push_line("INSERT INTO t\n");
+    assert!(!buffer.push_line("\n"));
+    assert!(!buffer.push_line("VALUES (1)\n"));
+    // 非连续的空行不会取消
+    assert!(!buffer.push_line("\n"));
+    assert!(buffer.push_line("  \n"));
+    assert_eq!(buffer.as_str(), "");
+
+    // 取消后重新计数
+    assert!(!buffer.push_line("\n"));
+}
+
+#[test]
+fn load_line_is_parsed_into_a_row() {
+    assert_eq!(repl::parse_load_line("1,Alice", 2).unwrap(), vec!["1", "Alice"]);
+    assert_eq!(repl::parse_load_line("2,\"Smith, Bob\"", 2).unwrap(), vec!["2", "Smith, Bob"]);
+    assert_eq!(repl::parse_load_line("3,", 2).unwrap(), vec!["3", ""]);
+
+    assert_eq!(repl::parse_load_line("4,Carol,extra", 2).unwrap_err(), "Expected 2 values, got 3");
+    assert!(repl::parse_load_line("5,\"unterminated", 2).is_err());
+}
+
+#[test]
+fn load_rows_stops_at_terminator_and_skips_bad_lines() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+
+    let input = "1,Alice\nnot-a-row\n2,Bob\nx,Carol\n\\.\n3,After\n";
+    let (ok, failed) = repl::load_rows(input.as_bytes(), &mut db, "USERS").unwrap();
+    assert_eq!((ok, failed), (2, 2));
+    assert_eq!(db.row_count("users"), Some(2));
+
+    // 整个导入只有一个快照，UNDO 一次全部撤销
+    assert_eq!(db.snapshots.len(), 1);
+    db.undo().unwrap();
+    assert_eq!(db.row_count("users"), Some(0));
+
+    assert!(repl::load_rows("1,x\n".as_bytes(), &mut db, "missing").is_err());
+}
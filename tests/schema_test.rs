@@ -0,0 +1,84 @@
+use rustique_db::database::{DataType, Database, Join, JoinCondition, JoinKind};
+
+mod common;
+use common::{run, run_select};
+
+fn rows(data: &[&[&str]]) -> Vec<Vec<String>> {
+    data.iter().map(|r| r.iter().map(|s| s.to_string()).collect()).collect()
+}
+
+// sales.orders 和 hr.orders 同名但属于不同模式
+fn setup() -> Database {
+    let mut db = Database::new();
+    for table in ["sales.orders", "hr.orders"] {
+        db.create_table(table, vec![
+            ("id", DataType::Int(10), true, true),
+            ("owner", DataType::Varchar(20), false, false),
+        ]).unwrap();
+    }
+    db.insert("sales.orders", None, vec![vec!["1", "shop"]]).unwrap();
+    db.insert("hr.orders", None, vec![vec!["1", "alice"], vec!["2", "bob"]]).unwrap();
+    db
+}
+
+#[test]
+fn same_table_name_in_two_schemas() {
+    let mut db = setup();
+    assert_eq!(db.row_count("sales.orders"), Some(1));
+    assert_eq!(db.row_count("hr.orders"), Some(2));
+    assert!(db.table("orders").is_none());
+
+    // 列可以用 表名.列 或 模式.表名.列 限定
+    let owners = db.select("hr.orders", vec!["orders.owner"], Some("hr.orders.id = 2"), None, None, None).unwrap();
+    assert_eq!(owners, rows(&[&["bob"]]));
+
+    db.update("hr.orders", vec![("owner".into(), "carol".into())], Some("orders.id = 1")).unwrap();
+    db.delete("sales.orders", Some("sales.orders.id = 1")).unwrap();
+    assert_eq!(db.row_count("sales.orders"), Some(0));
+    assert_eq!(db.select("hr.orders", vec!["owner"], None, None, None, None).unwrap(), rows(&[&["carol"], &["bob"]]));
+
+    db.drop_tables(&["sales.orders".to_string()], false).unwrap();
+    assert!(db.table("sales.orders").is_none());
+    assert!(db.table("hr.orders").is_some());
+}
+
+#[test]
+fn join_across_schemas() {
+    let db = setup();
+    let joins = vec![Join {
+        kind: JoinKind::Inner,
+        table: "hr.orders".into(),
+        alias: None,
+        condition: JoinCondition::On(vec![("sales.orders.id".into(), "hr.orders.id".into())]),
+    }];
+    let joined = db.joined_table("sales.orders", None, &joins).unwrap();
+    let result = db.select_from(&joined, vec!["sales.orders.owner", "hr.orders.owner"], None, None, None, None).unwrap();
+    assert_eq!(result, rows(&[&["shop", "alice"]]));
+}
+
+#[test]
+fn invalid_schema_qualified_names_are_rejected() {
+    let mut db = Database::new();
+    for name in ["a.b.c", ".orders", "sales."] {
+        let err = db.create_table(name, vec![("id", DataType::Int(10), false, false)]).unwrap_err();
+        assert!(err.contains("Invalid table name"), "{}", err);
+    }
+}
+
+#[test]
+fn schema_qualified_tables_from_sql() {
+    let mut db = Database::new();
+    run(&mut db, "CREATE TABLE sales.orders (id INT PRIMARY KEY, owner VARCHAR(20))").unwrap();
+    run(&mut db, "CREATE TABLE hr.orders (id INT PRIMARY KEY, owner VARCHAR(20))").unwrap();
+    run(&mut db, "INSERT INTO sales.orders VALUES (1, 'shop')").unwrap();
+    run(&mut db, "INSERT INTO hr.orders VALUES (1, 'alice'), (2, 'bob')").unwrap();
+    run(&mut db, "UPDATE hr.orders SET owner = 'carol' WHERE id = 2").unwrap();
+    run(&mut db, "DELETE FROM sales.orders WHERE sales.orders.id = 1").unwrap();
+
+    assert_eq!(run_select(&db, "SELECT * FROM sales.orders").unwrap(), Vec::<Vec<String>>::new());
+    assert_eq!(run_select(&db, "SELECT owner FROM hr.orders ORDER BY id").unwrap(), rows(&[&["alice"], &["carol"]]));
+
+    run(&mut db, "DROP TABLE hr.orders").unwrap();
+    assert!(db.table("hr.orders").is_none());
+    assert!(db.table("sales.orders").is_some());
+}
@@ -0,0 +1,646 @@
+use rustique_db::database::{DataType, Database};
+use rustique_db::parser::{parse_sql, SqlAst};
+
+mod common;
+use common::run_select;
+
+fn setup_users() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, true),
+        ("age", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![
+        vec!["1", "Alice", "30"],
+        vec!["2", "Bob", "25"],
+        vec!["3", "Carol", "41"],
+        vec!["4", "Dave", "19"],
+        vec!["5", "Eve", "35"],
+    ]).unwrap();
+    db
+}
+
+#[test]
+fn order_by_desc_lowercase_with_limit() {
+    let db = setup_users();
+    let rows = run_select(&db, "SELECT name, age FROM users ORDER BY age desc LIMIT 2").unwrap();
+    assert_eq!(rows, vec![
+        vec!["Carol".to_string(), "41".to_string()],
+        vec!["Eve".to_string(), "35".to_string()],
+    ]);
+}
+
+#[test]
+fn order_by_mixed_case_direction() {
+    let db = setup_users();
+    let asc = run_select(&db, "SELECT name FROM users ORDER BY age Asc LIMIT 1").unwrap();
+    assert_eq!(asc, vec![vec!["Dave".to_string()]]);
+
+    let desc = run_select(&db, "select name from users order by age DeSc limit 1").unwrap();
+    assert_eq!(desc, vec![vec!["Carol".to_string()]]);
+}
+
+#[test]
+fn concat_columns_with_literal_separator() {
+    let mut db = Database::new();
+    db.create_table("people", vec![
+        ("first", DataType::Varchar(20), false, false),
+        ("last", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("people", None, vec![
+        vec!["Ada", "Lovelace"],
+        vec!["Alan", ""],
+    ]).unwrap();
+
+    let rows = run_select(&db, "SELECT CONCAT(first, ' ', last) FROM people").unwrap();
+    assert_eq!(rows, vec![
+        vec!["Ada Lovelace".to_string()],
+        vec!["Alan ".to_string()],  // NULL 视为空字符串
+    ]);
+}
+
+#[test]
+fn concat_header_uses_expression_or_alias() {
+    match parse_sql("SELECT CONCAT(first, last), CONCAT(last, first) AS rev FROM people").unwrap() {
+        SqlAst::Select { columns, aliases, .. } => {
+            assert_eq!(columns, vec!["CONCAT(first, last)", "CONCAT(last, first)"]);
+            assert_eq!(aliases, vec![None, Some("rev".to_string())]);
+        }
+        other => panic!("expected SELECT, got {:?}", other),
+    }
+}
+
+#[test]
+fn quoted_reserved_words_as_identifiers() {
+    let mut db = Database::new();
+    common::run(&mut db, r#"CREATE TABLE "orders" ("order" INT PRIMARY KEY, `group` VARCHAR(20))"#).unwrap();
+    common::run(&mut db, r#"INSERT INTO orders ("order", `group`) VALUES (1, 'a'), (2, 'b'), (3, 'a')"#).unwrap();
+
+    let table = db.table("orders").unwrap();
+    let columns: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
+    assert_eq!(table.name, "orders");
+    assert_eq!(columns, vec!["order", "group"]);
+
+    let rows = run_select(&db, r#"SELECT "order" FROM orders WHERE `group` = 'a' ORDER BY "order" DESC"#).unwrap();
+    assert_eq!(rows, vec![vec!["3".to_string()], vec!["1".to_string()]]);
+
+    let rows = run_select(&db, "SELECT CONCAT(`group`, \"order\") FROM orders WHERE \"order\" > 2").unwrap();
+    assert_eq!(rows, vec![vec!["a3".to_string()]]);
+}
+
+#[test]
+fn quoted_and_padded_table_names_are_normalized() {
+    let mut db = setup_users();
+    common::run(&mut db, r#"INSERT INTO " users " VALUES (9, 'Zoe', 20)"#).unwrap();
+    let rows = run_select(&db, r#"SELECT name FROM "users " WHERE id = 9"#).unwrap();
+    assert_eq!(rows, vec![vec!["Zoe".to_string()]]);
+    common::run(&mut db, "DELETE FROM `users ` WHERE id = 9").unwrap();
+    assert_eq!(db.row_count("users"), Some(5));
+
+    for sql in ["DESCRIBE `users`", r#"DESC " users ";"#, "DESCRIBE users ;"] {
+        match parse_sql(sql).unwrap() {
+            SqlAst::Describe { table } => assert_eq!(table, "users", "{}", sql),
+            other => panic!("expected DESCRIBE, got {:?}", other),
+        }
+    }
+    for sql in ["DELETE ` users ` WHERE id = 1", r#"UPDATE " users" SET age = 1"#] {
+        match parse_sql(sql).unwrap() {
+            SqlAst::Delete { table, .. } | SqlAst::Update { table, .. } => assert_eq!(table, "users", "{}", sql),
+            other => panic!("expected DELETE or UPDATE, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn in_lists_mixed_with_parenthesized_groups() {
+    let db = setup_users();
+    let names = |cond: &str| -> Vec<String> {
+        db.select("users", vec!["name"], Some(cond), Some(vec![("id", false)]), None, None)
+            .unwrap()
+            .into_iter()
+            .map(|mut row| row.remove(0))
+            .collect()
+    };
+
+    assert_eq!(names("id IN (1, 2, 3)"), vec!["Alice", "Bob", "Carol"]);
+    assert_eq!(names("(id IN (1, 2, 3))"), vec!["Alice", "Bob", "Carol"]);
+    assert_eq!(names("(age > 20 AND id IN (1, 3, 4)) AND name IN ('Carol', 'Dave')"), vec!["Carol"]);
+    assert_eq!(names("id NOT IN (2, 4) AND (age < 40 AND (name IN ('Alice', 'Eve')))"), vec!["Alice", "Eve"]);
+    // 列表项本身带括号，引号内的括号和逗号不影响分组
+    assert_eq!(names("id IN ((1), (5)) AND (age > 0)"), vec!["Alice", "Eve"]);
+    assert_eq!(names("(name IN ('Bob', 'a (b, c)')) AND (id IN (2))"), vec!["Bob"]);
+    assert!(db.select("users", vec!["name"], Some("(id = 1) = 1"), None, None, None).is_err());
+}
+
+#[test]
+fn mysql_limit_comma_matches_limit_offset() {
+    let db = setup_users();
+    let comma = run_select(&db, "SELECT id FROM users ORDER BY id LIMIT 1, 2").unwrap();
+    let standard = run_select(&db, "SELECT id FROM users ORDER BY id LIMIT 2 OFFSET 1").unwrap();
+
+    assert_eq!(comma, vec![vec!["2".to_string()], vec!["3".to_string()]]);
+    assert_eq!(comma, standard);
+}
+
+#[test]
+fn offset_fetch_matches_limit_offset() {
+    let db = setup_users();
+    let fetch = run_select(&db, "SELECT id FROM users ORDER BY id OFFSET 1 ROWS FETCH NEXT 2 ROWS ONLY").unwrap();
+    let standard = run_select(&db, "SELECT id FROM users ORDER BY id LIMIT 2 OFFSET 1").unwrap();
+    assert_eq!(fetch, vec![vec!["2".to_string()], vec!["3".to_string()]]);
+    assert_eq!(fetch, standard);
+
+    // 省略行数时只取一行
+    let first = run_select(&db, "SELECT id FROM users ORDER BY id FETCH FIRST ROW ONLY").unwrap();
+    assert_eq!(first, vec![vec!["1".to_string()]]);
+    assert!(parse_sql("SELECT id FROM users FETCH FIRST 50 PERCENT ROWS ONLY").is_err());
+}
+
+#[test]
+fn top_matches_limit() {
+    let db = setup_users();
+    let top = run_select(&db, "SELECT TOP 3 * FROM users").unwrap();
+    assert_eq!(top, run_select(&db, "SELECT * FROM users LIMIT 3").unwrap());
+    assert_eq!(top.len(), 3);
+
+    // 先排序再取前 n 行
+    let oldest = run_select(&db, "SELECT TOP 2 name FROM users ORDER BY age DESC").unwrap();
+    assert_eq!(oldest, vec![vec!["Carol".to_string()], vec!["Eve".to_string()]]);
+    assert_eq!(oldest, run_select(&db, "SELECT name FROM users ORDER BY age DESC LIMIT 2").unwrap());
+
+    assert!(parse_sql("SELECT TOP 2 * FROM users LIMIT 3").is_err());
+}
+
+#[test]
+fn offset_past_end_returns_no_rows() {
+    let db = setup_users();
+    let rows = run_select(&db, "SELECT id FROM users LIMIT 10 OFFSET 20").unwrap();
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn extreme_limit_and_offset_do_not_overflow() {
+    let db = setup_users();
+    let order = || Some(vec![("id", false)]);
+    let ids = |rows: Vec<Vec<String>>| rows.into_iter().map(|mut r| r.remove(0)).collect::<Vec<_>>();
+
+    // 排序后取窗口：OFFSET 超过行数时为空，LIMIT 很大时取到末尾
+    assert!(db.select("users", vec!["id"], None, order(), Some(usize::MAX), Some(usize::MAX)).unwrap().is_empty());
+    assert!(db.select("users", vec!["id"], None, order(), None, Some(6)).unwrap().is_empty());
+    assert_eq!(ids(db.select("users", vec!["id"], None, order(), Some(usize::MAX), Some(3)).unwrap()), vec!["4", "5"]);
+    assert!(db.select_grouped("users", vec!["age", "COUNT(*)"], None, vec!["age"], Some(vec![("age", false)]), Some(usize::MAX), Some(usize::MAX))
+        .unwrap()
+        .is_empty());
+
+    let joined = db.joined_table("users", Some("u"), &[]).unwrap();
+    assert!(db.select_from(&joined, vec!["u.id"], None, None, Some(1), Some(usize::MAX - 1)).unwrap().is_empty());
+}
+
+#[test]
+fn oversized_limit_values_are_clamped() {
+    for sql in [
+        "SELECT id FROM users LIMIT 99999999999999999999999999 OFFSET 99999999999999999999999999",
+        "SELECT id FROM users OFFSET 99999999999999999999999999 ROWS FETCH NEXT 1 ROWS ONLY",
+    ] {
+        match parse_sql(sql).unwrap() {
+            SqlAst::Select { offset, .. } => assert_eq!(offset, Some(usize::MAX), "{}", sql),
+            other => panic!("expected SELECT, got {:?}", other),
+        }
+    }
+    let db = setup_users();
+    assert!(run_select(&db, "SELECT id FROM users ORDER BY id LIMIT 18446744073709551615 OFFSET 18446744073709551615").unwrap().is_empty());
+
+    let err = parse_sql("SELECT id FROM users LIMIT 1 OFFSET -5").unwrap_err();
+    assert!(err.contains("OFFSET must not be negative"), "{}", err);
+}
+
+#[test]
+fn order_by_random_is_deterministic_with_seed() {
+    let mut db = setup_users();
+    db.set_random_seed(Some(42));
+
+    let first = run_select(&db, "SELECT id FROM users ORDER BY RANDOM()").unwrap();
+    let second = run_select(&db, "SELECT id FROM users ORDER BY RAND()").unwrap();
+    assert_eq!(first, second);
+
+    // 洗牌结果是原数据的一个排列
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(sorted, (1..=5).map(|i| vec![i.to_string()]).collect::<Vec<_>>());
+
+    // LIMIT 取洗牌后的前 N 行
+    let sample = run_select(&db, "SELECT id FROM users ORDER BY RANDOM() LIMIT 2").unwrap();
+    assert_eq!(sample, first[..2].to_vec());
+}
+
+#[test]
+fn random_shuffle_differs_between_seeds() {
+    let mut db = setup_users();
+    let orders: Vec<Vec<Vec<String>>> = (0..5u64).map(|seed| {
+        db.set_random_seed(Some(seed));
+        db.select("users", vec!["id"], None, Some(vec![("RANDOM()", false)]), None, None).unwrap()
+    }).collect();
+    assert!(orders.windows(2).any(|w| w[0] != w[1]));
+}
+
+#[test]
+fn cast_int_column_to_varchar_in_projection() {
+    let db = setup_users();
+    let rows = run_select(&db, "SELECT name, CAST(age AS VARCHAR) FROM users WHERE id = 1").unwrap();
+    assert_eq!(rows, vec![vec!["Alice".to_string(), "30".to_string()]]);
+}
+
+#[test]
+fn cast_varchar_column_to_int_in_condition() {
+    let mut db = Database::new();
+    db.create_table("codes", vec![("code", DataType::Varchar(10), false, false)]).unwrap();
+    db.insert("codes", None, vec![vec!["3"], vec!["12"], vec!["7"]]).unwrap();
+
+    let rows = db.select("codes", vec!["code"], Some("CAST(code AS INT) > 5"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["12".to_string()], vec!["7".to_string()]]);
+
+    // 非数字字符串无法转换为 INT
+    db.insert("codes", None, vec![vec!["abc"]]).unwrap();
+    let err = db.select("codes", vec!["code"], Some("CAST(code AS INT) > 5"), None, None, None).unwrap_err();
+    assert!(err.contains("Cannot cast 'abc' to INT"), "{}", err);
+    let err = db.select("codes", vec!["CAST(code AS INT)"], None, None, None, None).unwrap_err();
+    assert!(err.contains("Cannot cast 'abc' to INT"), "{}", err);
+}
+
+#[test]
+fn compare_two_int_columns() {
+    let mut db = Database::new();
+    db.create_table("depts", vec![
+        ("name", DataType::Varchar(20), false, false),
+        ("salary", DataType::Int(10), false, false),
+        ("budget", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("depts", None, vec![
+        vec!["ops", "900", "1000"],
+        vec!["dev", "1200", "1000"],
+        vec!["qa", "80", "700"],
+        vec!["hr", "500", "500"],
+    ]).unwrap();
+
+    // 数值比较：80 < 700，虽然按字符串 "80" > "700"
+    let over = db.select("depts", vec!["name"], Some("salary > budget"), None, None, None).unwrap();
+    assert_eq!(over, vec![vec!["dev".to_string()]]);
+    let under = db.select("depts", vec!["name"], Some("salary < budget"), None, None, None).unwrap();
+    assert_eq!(under, vec![vec!["ops".to_string()], vec!["qa".to_string()]]);
+    let equal = db.select("depts", vec!["name"], Some("salary = budget"), None, None, None).unwrap();
+    assert_eq!(equal, vec![vec!["hr".to_string()]]);
+}
+
+#[test]
+fn compare_two_varchar_columns() {
+    let mut db = Database::new();
+    db.create_table("pairs", vec![
+        ("a", DataType::Varchar(10), false, false),
+        ("b", DataType::Varchar(10), false, false),
+    ]).unwrap();
+    db.insert("pairs", None, vec![
+        vec!["apple", "banana"],
+        vec!["pear", "pear"],
+        vec!["80", "700"],
+        vec!["b", ""],
+    ]).unwrap();
+
+    let less = db.select("pairs", vec!["a"], Some("a < b"), None, None, None).unwrap();
+    assert_eq!(less, vec![vec!["apple".to_string()]]);
+    let same = db.select("pairs", vec!["a"], Some("a = b"), None, None, None).unwrap();
+    assert_eq!(same, vec![vec!["pear".to_string()]]);
+
+    // 加引号时按字面量比较
+    let literal = db.select("pairs", vec!["a"], Some("a = 'b'"), None, None, None).unwrap();
+    assert_eq!(literal, vec![vec!["b".to_string()]]);
+}
+
+#[test]
+fn order_by_column_not_in_projection() {
+    let db = setup_users();
+
+    let rows = db.select("users", vec!["name"], None, Some(vec![("age", false)]), None, None).unwrap();
+    let names: Vec<&str> = rows.iter().map(|r| r[0].as_str()).collect();
+    assert_eq!(names, vec!["Dave", "Bob", "Alice", "Eve", "Carol"]);
+
+    let rows = run_select(&db, "SELECT name FROM users ORDER BY age DESC LIMIT 2").unwrap();
+    assert_eq!(rows, vec![vec!["Carol".to_string()], vec!["Eve".to_string()]]);
+}
+
+#[test]
+fn table_alias_without_as() {
+    let db = setup_users();
+    let rows = run_select(&db, "SELECT u.name FROM users u WHERE u.age > 30 ORDER BY u.id").unwrap();
+    assert_eq!(rows, vec![vec!["Carol".to_string()], vec!["Eve".to_string()]]);
+}
+
+#[test]
+fn table_alias_with_as() {
+    let db = setup_users();
+    let rows = run_select(&db, "SELECT u.id, name FROM users AS u WHERE u.name = 'Bob'").unwrap();
+    assert_eq!(rows, vec![vec!["2".to_string(), "Bob".to_string()]]);
+
+    // 使用别名后原表名不再可用作限定符
+    assert!(run_select(&db, "SELECT users.name FROM users AS u").is_err());
+}
+
+#[test]
+fn empty_result_prints_headers_when_enabled() {
+    use rustique_db::format_select;
+    use rustique_db::repl::{self, ReplSettings};
+
+    let mut db = setup_users();
+    let mut settings = ReplSettings::default();
+    let sql = "SELECT id, name FROM users WHERE age > 100";
+    assert_eq!(format_select(&db, parse_sql(sql).unwrap(), &settings).unwrap(), None);
+
+    repl::handle_dot_command(".headers on", &mut db, &mut settings);
+    let output = format_select(&db, parse_sql(sql).unwrap(), &settings).unwrap().unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("id") && lines[0].contains("name"), "{}", output);
+}
+
+fn setup_paths() -> Database {
+    let mut db = Database::new();
+    db.create_table("files", vec![
+        ("path", DataType::Varchar(50), false, false),
+    ]).unwrap();
+    db.insert("files", None, vec![
+        vec!["50%_off"],
+        vec!["500_items"],
+        vec!["50_percent"],
+        vec![""],
+    ]).unwrap();
+    db
+}
+
+#[test]
+fn like_escape_matches_literal_percent() {
+    let db = setup_paths();
+    let rows = db.select("files", vec!["path"], Some(r"path LIKE '50\%%'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["50%_off".to_string()]]);
+
+    let rows = db.select("files", vec!["path"], Some(r"path LIKE '50\%%' ESCAPE '\'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["50%_off".to_string()]]);
+
+    // 不转义时 % 是通配符
+    let all = db.select("files", vec!["path"], Some("path LIKE '50%'"), None, None, None).unwrap();
+    assert_eq!(all.len(), 3);
+}
+
+#[test]
+fn like_with_custom_escape_character() {
+    let db = setup_paths();
+    let rows = db.select("files", vec!["path"], Some("path LIKE '50!_%' ESCAPE '!'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["50_percent".to_string()]]);
+
+    // NULL 不匹配 NOT LIKE
+    let rows = db.select("files", vec!["path"], Some("path NOT LIKE '%!%%' ESCAPE '!'"), None, None, None).unwrap();
+    assert_eq!(rows.len(), 2);
+
+    let err = db.select("files", vec!["path"], Some("path LIKE 'x' ESCAPE '!!'"), None, None, None).unwrap_err();
+    assert!(err.contains("ESCAPE"), "{}", err);
+}
+
+#[test]
+fn ilike_ignores_case_but_like_does_not() {
+    let db = setup_users();
+    let rows = db.select("users", vec!["name"], Some("name ILIKE 'al%'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["Alice".to_string()]]);
+
+    let rows = db.select("users", vec!["name"], Some("name LIKE 'al%'"), None, None, None).unwrap();
+    assert!(rows.is_empty());
+
+    let rows = db.select("users", vec!["name"], Some("name not ilike '%E'"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["Bob".to_string()], vec!["Carol".to_string()]]);
+}
+
+#[test]
+fn is_true_and_is_false_predicates() {
+    let mut db = Database::new();
+    db.create_table("flags", vec![
+        ("id", DataType::Int(10), true, true),
+        ("active", DataType::Int(1), false, false),
+    ]).unwrap();
+    db.insert("flags", None, vec![vec!["1", "1"], vec!["2", "0"], vec!["3", ""]]).unwrap();
+
+    let ids = |cond: &str| -> Vec<Vec<String>> {
+        db.select("flags", vec!["id"], Some(cond), None, None, None).unwrap()
+    };
+    assert_eq!(ids("active IS TRUE"), vec![vec!["1".to_string()]]);
+    assert_eq!(ids("active IS FALSE"), vec![vec!["2".to_string()]]);
+    // NULL 属于 IS NOT TRUE 和 IS NOT FALSE
+    assert_eq!(ids("active IS NOT TRUE"), vec![vec!["2".to_string()], vec!["3".to_string()]]);
+    assert_eq!(ids("active IS NOT FALSE"), vec![vec!["1".to_string()], vec!["3".to_string()]]);
+}
+
+#[test]
+fn limit_zero_returns_empty_result() {
+    let db = setup_users();
+    let rows = db.select("users", vec!["name"], None, None, Some(0), None).unwrap();
+    assert!(rows.is_empty());
+
+    let rows = run_select(&db, "SELECT name FROM users LIMIT 0").unwrap();
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn negative_or_invalid_limit_is_an_error() {
+    let err = parse_sql("SELECT name FROM users LIMIT -1").unwrap_err();
+    assert!(err.contains("must not be negative"), "{}", err);
+
+    let err = parse_sql("SELECT name FROM users LIMIT 1.5").unwrap_err();
+    assert!(err.contains("Invalid LIMIT value"), "{}", err);
+
+    assert!(parse_sql("SELECT name FROM users LIMIT 'ten'").is_err());
+}
+
+#[test]
+fn column_names_are_case_insensitive_in_every_clause() {
+    let mut db = setup_users();
+    let rows = db.select("users", vec!["NAME", "Age"], Some("AGE > 30"), Some(vec![("aGe", true)]), None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["Carol".to_string(), "41".to_string()],
+        vec!["Eve".to_string(), "35".to_string()],
+    ]);
+
+    db.insert("users", Some(vec!["ID".into(), "Name".into()]), vec![vec!["6", "Frank"]]).unwrap();
+    assert_eq!(db.count_where("users", "NAME = 'Frank'").unwrap(), 1);
+
+    let err = db.create_table("dup", vec![
+        ("id", DataType::Int(10), false, false),
+        ("ID", DataType::Int(10), false, false),
+    ]).unwrap_err();
+    assert!(err.contains("Duplicate column"), "{}", err);
+}
+
+#[test]
+fn long_and_chain_evaluates_without_recursion() {
+    let db = setup_users();
+    let predicates: Vec<String> = (0..500).map(|i| format!("age > {}", i % 20)).collect();
+    let cond = predicates.join(" AND ");
+    let rows = db.select("users", vec!["name"], Some(&cond), None, None, None).unwrap();
+    assert_eq!(rows.len(), 4);
+
+    // AND 出现在字符串中时不切分；以 a 开头的列名不受影响
+    assert_eq!(db.count_where("users", "name = 'Alice AND Bob'").unwrap(), 0);
+    assert_eq!(db.count_where("users", "age > 20 and age < 40").unwrap(), 3);
+    assert!(db.count_where("users", "age > 20 AND").is_err());
+}
+
+#[test]
+fn custom_function_in_projection() {
+    let mut db = setup_users();
+    db.register_function("initials", Box::new(|args: &[String]| {
+        args.iter().filter_map(|a| a.chars().next()).collect()
+    })).unwrap();
+
+    let rows = db.select("users", vec!["Initials(name, age)"], Some("id < 3"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["A3".to_string()], vec!["B2".to_string()]]);
+
+    // 内置函数不能被覆盖，未注册的函数仍然报错
+    assert!(db.register_function("concat", Box::new(|_: &[String]| String::new())).is_err());
+    assert!(db.select("users", vec!["MYHASH(name)"], None, None, None, None).is_err());
+}
+
+#[test]
+fn star_followed_by_computed_column() {
+    let db = setup_users();
+    let rows = db.select("users", vec!["*", "CONCAT(name, '!')"], Some("id = 1"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["1", "Alice", "30", "Alice!"]]);
+
+    let select = SqlAst::Select {
+        table: "users".into(),
+        alias: None,
+        joins: vec![],
+        columns: vec!["*".into(), "CONCAT(name, '!')".into()],
+        aliases: vec![None, Some("shout".into())],
+        where_clause: Some("id = 2".into()),
+        group_by: vec![],
+        order_by: vec![],
+        limit: None,
+        offset: None,
+        distinct_on: vec![],
+    };
+    let (headers, rows) = rustique_db::select_rows(&db, select).unwrap();
+    assert_eq!(headers, vec!["id", "name", "age", "shout"]);
+    assert_eq!(rows, vec![vec!["2", "Bob", "25", "Bob!"]]);
+}
+
+#[test]
+fn star_in_the_middle_of_projection() {
+    let db = setup_users();
+    let rows = db.select("users", vec!["name", "*", "id"], Some("id = 3"), None, None, None).unwrap();
+    assert_eq!(rows, vec![vec!["Carol", "3", "Carol", "41", "3"]]);
+}
+
+fn setup_events() -> Database {
+    let mut db = Database::new();
+    db.create_table("events", vec![
+        ("id", DataType::Int(10), true, true),
+        ("user_id", DataType::Int(10), false, true),
+        ("created", DataType::Int(10), false, true),
+    ]).unwrap();
+    db.insert("events", None, vec![
+        vec!["1", "1", "100"],
+        vec!["2", "2", "150"],
+        vec!["3", "1", "300"],
+        vec!["4", "2", "120"],
+        vec!["5", "3", "50"],
+        vec!["6", "1", "200"],
+    ]).unwrap();
+    db
+}
+
+fn distinct_on_select(order_by: Vec<(&str, bool)>, limit: Option<usize>) -> SqlAst {
+    SqlAst::Select {
+        table: "events".into(),
+        alias: None,
+        joins: vec![],
+        columns: vec!["*".into()],
+        aliases: vec![None],
+        where_clause: None,
+        group_by: vec![],
+        order_by: order_by.into_iter().map(|(c, d)| (c.to_string(), d)).collect(),
+        limit,
+        offset: None,
+        distinct_on: vec!["user_id".into()],
+    }
+}
+
+#[test]
+fn distinct_on_keeps_latest_row_per_key() {
+    let db = setup_events();
+    let (headers, rows) = rustique_db::select_rows(&db, distinct_on_select(vec![("created", true)], None)).unwrap();
+    assert_eq!(headers, vec!["id", "user_id", "created"]);
+    assert_eq!(rows, vec![
+        vec!["3", "1", "300"],
+        vec!["2", "2", "150"],
+        vec!["5", "3", "50"],
+    ]);
+
+    // 排序方向决定保留哪一行；LIMIT 作用于去重后的结果
+    let (_, rows) = rustique_db::select_rows(&db, distinct_on_select(vec![("created", false)], Some(2))).unwrap();
+    assert_eq!(rows, vec![vec!["5", "3", "50"], vec!["1", "1", "100"]]);
+}
+
+#[test]
+fn distinct_on_is_parsed() {
+    let ast = parse_sql("SELECT DISTINCT ON (user_id) * FROM events ORDER BY created DESC").unwrap();
+    match ast {
+        SqlAst::Select { distinct_on, .. } => assert_eq!(distinct_on, vec!["user_id"]),
+        other => panic!("expected SELECT, got {:?}", other),
+    }
+}
+
+fn setup_nullable() -> Database {
+    let mut db = Database::new();
+    db.create_table("items", vec![
+        ("id", DataType::Int(10), true, true),
+        ("qty", DataType::Int(10), false, false),
+        ("label", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("items", None, vec![
+        vec!["1", "5", "b"],
+        vec!["2", "", "a"],
+        vec!["3", "0", ""],
+        vec!["4", "-2", "c"],
+        vec!["5", "", ""],
+    ]).unwrap();
+    db
+}
+
+fn ids(rows: Vec<Vec<String>>) -> Vec<String> {
+    rows.into_iter().map(|row| row[0].clone()).collect()
+}
+
+#[test]
+fn nulls_sort_together_for_int_and_varchar_columns() {
+    let db = setup_nullable();
+    // 默认 NULL 最小：升序在前，降序在后，且不会和 0 混在一起
+    let by_qty = db.select("items", vec!["id"], None, Some(vec![("qty", false), ("id", false)]), None, None).unwrap();
+    assert_eq!(ids(by_qty), vec!["2", "5", "4", "3", "1"]);
+    let by_label = db.select("items", vec!["id"], None, Some(vec![("label", true), ("id", false)]), None, None).unwrap();
+    assert_eq!(ids(by_label), vec!["4", "1", "2", "3", "5"]);
+}
+
+#[test]
+fn nulls_first_and_last_override_default_placement() {
+    let db = setup_nullable();
+    let rows = db.select("items", vec!["id"], None, Some(vec![("qty NULLS LAST", false), ("id", false)]), None, None).unwrap();
+    assert_eq!(ids(rows), vec!["4", "3", "1", "2", "5"]);
+    let rows = db.select("items", vec!["id"], None, Some(vec![("qty nulls first", true), ("id", false)]), None, None).unwrap();
+    assert_eq!(ids(rows), vec!["2", "5", "1", "3", "4"]);
+    let rows = db.select("items", vec!["id"], None, Some(vec![("label NULLS LAST", false), ("id", false)]), None, None).unwrap();
+    assert_eq!(ids(rows), vec!["2", "1", "4", "3", "5"]);
+}
+
+#[test]
+fn nulls_placement_in_grouped_queries() {
+    let db = setup_nullable();
+    let rows = db.select_grouped("items", vec!["label", "COUNT(*)"], None, vec!["label"], Some(vec![("label NULLS LAST", false)]), None, None).unwrap();
+    let labels: Vec<&str> = rows.iter().map(|r| r[0].as_str()).collect();
+    assert_eq!(labels, vec!["a", "b", "c", ""]);
+}
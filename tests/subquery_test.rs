@@ -0,0 +1,99 @@
+use rustique_db::database::{DataType, Database};
+
+mod common;
+use common::{run, run_select};
+
+fn setup() -> Database {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, false),
+    ]).unwrap();
+    db.insert("users", None, vec![
+        vec!["1", "Alice"],
+        vec!["2", "Bob"],
+        vec!["3", "Carol"],
+        vec!["4", "Dave"],
+    ]).unwrap();
+
+    db.create_table("orders", vec![
+        ("id", DataType::Int(10), true, true),
+        ("user_id", DataType::Int(10), false, false),
+        ("total", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("orders", None, vec![
+        vec!["10", "1", "100"],
+        vec!["11", "3", "200"],
+        vec!["12", "1", "300"],
+        vec!["13", "4", "50"],
+    ]).unwrap();
+    db
+}
+
+fn names(rows: Vec<Vec<String>>) -> Vec<String> {
+    rows.into_iter().map(|mut r| r.remove(0)).collect()
+}
+
+#[test]
+fn in_subquery_matches_several_ids() {
+    let db = setup();
+    let rows = db.select("users", vec!["name"], Some("id IN (SELECT user_id FROM orders)"), None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["Alice", "Carol", "Dave"]);
+
+    let rows = run_select(&db, "SELECT name FROM users WHERE id IN (SELECT user_id FROM orders WHERE total > 150)").unwrap();
+    assert_eq!(names(rows), vec!["Alice", "Carol"]);
+}
+
+#[test]
+fn empty_subquery_matches_nothing() {
+    let db = setup();
+    let rows = db.select("users", vec!["name"], Some("id IN (SELECT user_id FROM orders WHERE total > 1000)"), None, None, None).unwrap();
+    assert!(rows.is_empty());
+
+    let rows = db.select("users", vec!["name"], Some("id NOT IN (SELECT user_id FROM orders)"), None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["Bob"]);
+}
+
+#[test]
+fn in_list_of_literals() {
+    let db = setup();
+    let rows = db.select("users", vec!["name"], Some("name IN ('Bob', 'Dave')"), None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["Bob", "Dave"]);
+}
+
+#[test]
+fn subquery_search_skips_literals_and_non_ascii_text() {
+    let db = setup();
+    // 引号内的 (select ...) 只是普通字符串
+    let rows = db.select("users", vec!["name"], Some("name = '(select id FROM orders)'"), None, None, None).unwrap();
+    assert!(rows.is_empty());
+
+    // ǰ 转大写后字节数会变，子查询位置不能依赖大写后的串
+    let cond = "name IN ('ǰǰǰ', 'Alice', 'Bob') AND id IN (SELECT user_id FROM orders)";
+    let rows = db.select("users", vec!["name"], Some(cond), None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["Alice"]);
+}
+
+#[test]
+fn delete_with_subquery_condition() {
+    let mut db = setup();
+    assert_eq!(run(&mut db, "DELETE FROM users WHERE id NOT IN (SELECT user_id FROM orders)").unwrap(), 1);
+    let rows = db.select("users", vec!["name"], None, None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["Alice", "Carol", "Dave"]);
+
+    assert_eq!(db.delete_with_alias("users", Some("u"), Some("u.id IN (SELECT user_id FROM orders WHERE total > 150)")).unwrap(), 2);
+    let rows = db.select("users", vec!["name"], None, None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["Dave"]);
+}
+
+#[test]
+fn update_with_subquery_condition() {
+    let mut db = setup();
+    assert_eq!(run(&mut db, "UPDATE users SET name = 'x' WHERE id IN (SELECT user_id FROM orders WHERE total > 150)").unwrap(), 2);
+    let rows = db.select("users", vec!["name"], None, None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["x", "Bob", "x", "Dave"]);
+
+    assert_eq!(run(&mut db, "UPDATE users SET name = 'y' WHERE id = (SELECT user_id FROM orders WHERE id = 13)").unwrap(), 1);
+    let rows = db.select("users", vec!["name"], Some("id = 4"), None, None, None).unwrap();
+    assert_eq!(names(rows), vec!["y"]);
+}
@@ -0,0 +1,253 @@
+use rustique_db::database::{DataType, Database, SET_EXPRESSION_PREFIX};
+
+mod common;
+use common::run;
+
+fn setup_people() -> Database {
+    let mut db = Database::new();
+    db.create_table("people", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(50), false, false),
+        ("age", DataType::Int(10), false, false),
+    ]).unwrap();
+    db.insert("people", None, vec![
+        vec!["1", "O'Brien", "40"],
+        vec!["2", "Obrien", "22"],
+        vec!["3", "IS NULL", "31"],
+    ]).unwrap();
+    db
+}
+
+fn ages(db: &Database) -> Vec<Vec<String>> {
+    db.select("people", vec!["id", "age"], None, None, None, None).unwrap()
+}
+
+#[test]
+fn update_where_value_contains_apostrophe() {
+    let mut db = setup_people();
+    let updated = db.update("people", vec![("age".into(), "50".into())], Some("name = 'O''Brien'")).unwrap();
+    assert_eq!(updated, 1);
+    assert_eq!(ages(&db)[0], vec!["1".to_string(), "50".to_string()]);
+    assert_eq!(ages(&db)[1], vec!["2".to_string(), "22".to_string()]);
+}
+
+#[test]
+fn update_statement_with_escaped_apostrophe() {
+    let mut db = setup_people();
+    assert_eq!(run(&mut db, "UPDATE people SET age = 50 WHERE name = 'O''Brien'").unwrap(), 1);
+    assert_eq!(ages(&db)[0], vec!["1".to_string(), "50".to_string()]);
+}
+
+#[test]
+fn update_where_value_containing_is_null_text() {
+    let mut db = setup_people();
+    assert_eq!(run(&mut db, "UPDATE people SET age = 1 WHERE name = 'IS NULL'").unwrap(), 1);
+    assert_eq!(ages(&db)[2], vec!["3".to_string(), "1".to_string()]);
+}
+
+#[test]
+fn set_and_where_columns_are_case_insensitive() {
+    let mut db = setup_people();
+    let updated = db.update("people", vec![("AGE".into(), "41".into())], Some("Id = 1")).unwrap();
+    assert_eq!(updated, 1);
+    assert_eq!(ages(&db)[0], vec!["1".to_string(), "41".to_string()]);
+}
+
+#[test]
+fn set_expressions_use_values_from_before_the_update() {
+    let mut db = Database::new();
+    db.create_table("pairs", vec![
+        ("a", DataType::Varchar(10), false, false),
+        ("b", DataType::Varchar(10), false, false),
+    ]).unwrap();
+    db.insert("pairs", None, vec![vec!["x", "y"], vec!["1", "2"]]).unwrap();
+
+    let swap = vec![
+        ("a".to_string(), format!("{}b", SET_EXPRESSION_PREFIX)),
+        ("b".to_string(), format!("{}a", SET_EXPRESSION_PREFIX)),
+    ];
+    assert_eq!(db.update("pairs", swap, None).unwrap(), 2);
+    let rows = db.select("pairs", vec!["a", "b"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["y".to_string(), "x".to_string()],
+        vec!["2".to_string(), "1".to_string()],
+    ]);
+
+    assert_eq!(run(&mut db, "UPDATE pairs SET a = b, b = a WHERE a = 'y'").unwrap(), 1);
+    let rows = db.select("pairs", vec!["a", "b"], None, None, None, None).unwrap();
+    assert_eq!(rows[0], vec!["x".to_string(), "y".to_string()]);
+}
+
+#[test]
+fn primary_key_assignments_are_checked_after_evaluation() {
+    let mut db = setup_people();
+    // 表达式求得的新主键与未更新的行重复
+    let set = vec![("id".to_string(), format!("{}age", SET_EXPRESSION_PREFIX))];
+    run(&mut db, "UPDATE people SET age = 3 WHERE id = 1").unwrap();
+    let err = db.update("people", set, Some("id = 1")).unwrap_err();
+    assert_eq!(err, "Duplicate entry '3' for key 'PRIMARY'");
+    // 同一常量赋给多行时，本次更新的行之间也会重复
+    let err = run(&mut db, "UPDATE people SET id = 9 WHERE id > 1").unwrap_err();
+    assert_eq!(err, "Duplicate entry '9' for key 'PRIMARY'");
+    assert_eq!(ages(&db), vec![
+        vec!["1".to_string(), "3".to_string()],
+        vec!["2".to_string(), "22".to_string()],
+        vec!["3".to_string(), "31".to_string()],
+    ]);
+
+    // 主键互换、保持原值都不算重复
+    run(&mut db, "UPDATE people SET id = 1 WHERE id = 1").unwrap();
+    let set = vec![("id".to_string(), format!("{}age", SET_EXPRESSION_PREFIX)), ("age".to_string(), format!("{}id", SET_EXPRESSION_PREFIX))];
+    assert_eq!(db.update("people", set, Some("id IN (1, 3)")).unwrap(), 2);
+    let ids: Vec<String> = ages(&db).into_iter().map(|row| row[0].clone()).collect();
+    assert_eq!(ids, vec!["3", "2", "31"]);
+}
+
+#[test]
+fn progress_callback_reports_every_n_rows() {
+    use std::sync::{Arc, Mutex};
+
+    let mut db = Database::new();
+    db.create_table("nums", vec![("n", DataType::Int(10), false, false)]).unwrap();
+    let values: Vec<String> = (0..25).map(|i| i.to_string()).collect();
+    db.insert("nums", None, values.iter().map(|v| vec![v.as_str()]).collect()).unwrap();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    db.set_progress(Some((10, Box::new(move |n| sink.lock().unwrap().push(n)))));
+
+    db.update("nums", vec![("n".into(), "1".into())], Some("n < 5")).unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![10, 20]);
+
+    seen.lock().unwrap().clear();
+    assert_eq!(db.delete("nums", Some("n = 1")).unwrap(), 5);
+    assert_eq!(*seen.lock().unwrap(), vec![10, 20]);
+
+    seen.lock().unwrap().clear();
+    let csv: String = std::iter::once("n".to_string()).chain((0..25).map(|i| i.to_string())).collect::<Vec<_>>().join("\n");
+    assert_eq!(db.import_csv("nums", &csv).unwrap(), 25);
+    assert_eq!(*seen.lock().unwrap(), vec![10, 20]);
+
+    db.set_progress(None);
+    db.update("nums", vec![("n".into(), "2".into())], None).unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn trim_varchar_strips_padding_on_update() {
+    let mut db = setup_people();
+    db.update("people", vec![("name".into(), " Bob ".into())], Some("id = 2")).unwrap();
+    assert_eq!(db.select("people", vec!["name"], Some("id = 2"), None, None, None).unwrap(), vec![vec![" Bob "]]);
+
+    db.set_trim_varchar(true);
+    db.update("people", vec![("name".into(), " Bob ".into())], Some("id = 2")).unwrap();
+    assert_eq!(db.select("people", vec!["name"], Some("id = 2"), None, None, None).unwrap(), vec![vec!["Bob"]]);
+}
+
+#[test]
+fn set_nullable_columns_to_null() {
+    let mut db = setup_people();
+    // INT 列和 VARCHAR 列都可以设为 NULL
+    assert_eq!(db.update("people", vec![("age".into(), "".into()), ("name".into(), "".into())], Some("id = 1")).unwrap(), 1);
+    let row: Vec<bool> = db.scan("people").unwrap().next().unwrap().iter().map(|v| v.is_null()).collect();
+    assert_eq!(row, vec![false, true, true]);
+
+    assert_eq!(run(&mut db, "UPDATE people SET age = NULL WHERE id = 2").unwrap(), 1);
+    assert!(db.scan("people").unwrap().nth(1).unwrap()[2].is_null());
+}
+
+#[test]
+fn set_not_null_column_to_null_is_rejected() {
+    let mut db = Database::new();
+    db.create_table("accounts", vec![
+        ("id", DataType::Int(10), true, true),
+        ("owner", DataType::Varchar(20), false, true),
+    ]).unwrap();
+    db.insert("accounts", None, vec![vec!["1", "Alice"]]).unwrap();
+
+    let err = db.update("accounts", vec![("owner".into(), "".into())], None).unwrap_err();
+    assert_eq!(err, "Column 'owner' cannot be null");
+    let err = db.update("accounts", vec![("id".into(), "".into())], None).unwrap_err();
+    assert_eq!(err, "Column 'id' cannot be null");
+
+    assert!(run(&mut db, "UPDATE accounts SET owner = NULL").is_err());
+    // 字符串 'NULL' 不是 NULL
+    assert_eq!(run(&mut db, "UPDATE accounts SET owner = 'NULL'").unwrap(), 1);
+}
+
+#[test]
+fn update_varchar_length_counts_characters_not_bytes() {
+    let mut db = Database::new();
+    db.create_table("words", vec![
+        ("id", DataType::Int(10), true, true),
+        ("word", DataType::Varchar(3), false, false),
+    ]).unwrap();
+    db.insert("words", None, vec![vec!["1", "abc"]]).unwrap();
+
+    db.update("words", vec![("word".into(), "héé".into())], None).unwrap();
+    let err = db.update("words", vec![("word".into(), "éééé".into())], None).unwrap_err();
+    assert_eq!(err, "Value too long for column 'word' (max 3)");
+    assert_eq!(db.select("words", vec!["word"], None, None, None, None).unwrap(), vec![vec!["héé"]]);
+}
+
+#[test]
+fn update_with_invalid_condition_is_an_error() {
+    let mut db = setup_people();
+    assert!(db.update("people", vec![("name".into(), "y".into())], Some("nosuchcol = 1")).is_err());
+    assert!(run(&mut db, "UPDATE people SET name = 'y' WHERE nosuchcol = 1").is_err());
+    assert_eq!(db.select("people", vec!["name"], Some("id = 1"), None, None, None).unwrap(), vec![vec!["O'Brien"]]);
+}
+
+#[test]
+fn update_with_table_alias() {
+    let mut db = setup_people();
+    // SET 表达式和条件中的列都可以用别名限定
+    let set = vec![("age".to_string(), format!("{}p.age + 1", SET_EXPRESSION_PREFIX))];
+    assert_eq!(db.update_with_alias("people", Some("p"), set, Some("p.id = 2")).unwrap(), 1);
+    assert_eq!(ages(&db)[1], vec!["2".to_string(), "23".to_string()]);
+    let set = vec![("name".to_string(), format!("{}CONCAT(p.name, '!')", SET_EXPRESSION_PREFIX))];
+    assert_eq!(db.update_with_alias("people", Some("p"), set, Some("p.id = 3")).unwrap(), 1);
+
+    run(&mut db, "UPDATE people p SET p.age = 50 WHERE p.name = 'Obrien'").unwrap();
+    run(&mut db, "UPDATE people AS p SET name = 'Ann' WHERE p.id = 1 AND age > 30").unwrap();
+    let rows = db.select("people", vec!["name", "age"], None, None, None, None).unwrap();
+    assert_eq!(rows, vec![
+        vec!["Ann".to_string(), "40".to_string()],
+        vec!["Obrien".to_string(), "50".to_string()],
+        vec!["IS NULL!".to_string(), "31".to_string()],
+    ]);
+}
+
+#[test]
+fn update_with_table_alias_and_subquery_condition() {
+    let mut db = setup_people();
+    db.create_table("vips", vec![("person_id", DataType::Int(10), false, false)]).unwrap();
+    db.insert("vips", None, vec![vec!["1"], vec!["3"]]).unwrap();
+
+    let updated = run(&mut db, "UPDATE people p SET p.age = p.age - 1 WHERE p.id IN (SELECT person_id FROM vips)").unwrap();
+    assert_eq!(updated, 2);
+    assert_eq!(ages(&db), vec![
+        vec!["1".to_string(), "39".to_string()],
+        vec!["2".to_string(), "22".to_string()],
+        vec!["3".to_string(), "30".to_string()],
+    ]);
+}
+
+#[test]
+fn set_expressions_support_integer_addition_and_subtraction() {
+    let mut db = setup_people();
+    assert_eq!(run(&mut db, "UPDATE people SET id = id + 10").unwrap(), 3);
+    run(&mut db, "UPDATE people SET age = (age - 2) + id - -1 WHERE id = 11").unwrap();
+    assert_eq!(ages(&db), vec![
+        vec!["11".to_string(), "50".to_string()],
+        vec!["12".to_string(), "22".to_string()],
+        vec!["13".to_string(), "31".to_string()],
+    ]);
+
+    // NULL 参与运算结果为 NULL；文本不能参与运算
+    db.update("people", vec![("age".into(), "".into())], Some("id = 12")).unwrap();
+    run(&mut db, "UPDATE people SET age = age + 1 WHERE id = 12").unwrap();
+    assert_eq!(ages(&db)[1], vec!["12".to_string(), "".to_string()]);
+    let err = run(&mut db, "UPDATE people SET age = name + 1 WHERE id = 13").unwrap_err();
+    assert_eq!(err, "Value 'IS NULL' is not INT in column expression");
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use rustique_db::database::{DataType, Database, IntegrityError};
+use rustique_db::CommandHistory;
+
+// 把手工编辑过的 JSON 写入临时文件后加载
+fn load_json(name: &str, json: &str) -> Database {
+    let dir = std::env::temp_dir().join(format!("rustique_validate_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("db.json");
+    fs::write(&path, json).unwrap();
+    let db = Database::load_readonly_from(&path).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    db
+}
+
+#[test]
+fn consistent_database_passes() {
+    let mut db = Database::new();
+    db.create_table("users", vec![
+        ("id", DataType::Int(10), true, true),
+        ("name", DataType::Varchar(20), false, true),
+    ]).unwrap();
+    db.insert("users", None, vec![vec!["1", "Alice"], vec!["2", "Bob"]]).unwrap();
+    assert_eq!(db.validate(), Ok(()));
+}
+
+#[test]
+fn corrupted_rows_report_every_category() {
+    let db = load_json("corrupt", r#"{"tables":[{"name":"users","columns":[
+        {"name":"id","data_type":{"Int":10},"is_primary":true,"not_null":true},
+        {"name":"name","data_type":{"Varchar":3},"is_primary":false,"not_null":true}
+    ],"data":[
+        ["1","Amy"],
+        ["2"],
+        ["1","Bob"],
+        ["3",""],
+        ["4","Carol"]
+    ]}]}"#);
+
+    let errors = db.validate().unwrap_err();
+    assert_eq!(errors, vec![
+        IntegrityError::ArityMismatch { table: "users".into(), row: 2, expected: 2, found: 1 },
+        IntegrityError::DuplicateKey { table: "users".into(), key: "1".into(), first_row: 1, row: 3 },
+        IntegrityError::NullInNotNull { table: "users".into(), row: 4, column: "name".into() },
+        IntegrityError::TypeMismatch { table: "users".into(), row: 5, column: "name".into(), value: "Carol".into() },
+    ]);
+    assert_eq!(errors[1].to_string(), "Table 'users': duplicate primary key '1' (rows 1 and 3)");
+}
+
+#[test]
+fn strict_load_rejects_type_mismatches_that_lenient_load_keeps() {
+    let dir = std::env::temp_dir().join(format!("rustique_strict_load_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("db.json");
+    let history_path = dir.join("history.json");
+    fs::write(&db_path, r#"{"tables":[{"name":"items","columns":[
+        {"name":"id","data_type":{"Int":10},"is_primary":true,"not_null":true},
+        {"name":"qty","data_type":{"Int":10},"is_primary":false,"not_null":false}
+    ],"data":[["1","5"],["2","abc"],["x","7"]]}]}"#).unwrap();
+
+    // 严格模式一次列出所有不符的单元格
+    let mut history = CommandHistory::new(10);
+    let err = Database::load_strict_with_history_from(&db_path, &history_path, &mut history).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("row 2: value 'abc'") && message.contains("row 3: value 'x'"), "{}", message);
+
+    // 默认模式照常加载，不符的值保留为文本，由 validate() 报告
+    let db = Database::load_with_history_from(&db_path, &history_path, &mut history).unwrap();
+    assert_eq!(db.row_count("items"), Some(3));
+    assert_eq!(db.select("items", vec!["qty"], Some("id = 1"), None, None, None).unwrap(), vec![vec!["5".to_string()]]);
+    assert_eq!(db.validate().unwrap_err().len(), 2);
+    assert_eq!(db.type_mismatches(), vec![
+        IntegrityError::TypeMismatch { table: "items".into(), row: 2, column: "qty".into(), value: "abc".into() },
+        IntegrityError::TypeMismatch { table: "items".into(), row: 3, column: "id".into(), value: "x".into() },
+    ]);
+    drop(db);
+
+    let _ = fs::remove_dir_all(&dir);
+}